@@ -1,7 +1,8 @@
+use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 use cw_arcade::msg::{
-    AdminsListResp, ExecuteMsg, InstantiateMsg, QueryMsg, ScoreboardListResp,
+    AdminsListResp, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
+    ReceiveMsg, ScoreboardListResp,
 };
-use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 use std::{env::current_dir, fs::create_dir_all};
 
 fn main() {
@@ -13,6 +14,8 @@ fn main() {
     export_schema(&schema_for!(AdminsListResp), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
+    export_schema(&schema_for!(MigrateMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(ReceiveMsg), &out_dir);
     export_schema(&schema_for!(ScoreboardListResp), &out_dir);
 }
@@ -4,22 +4,22 @@ query="$1"
 
 case "$query" in
   "top-scores")
-    archway query contract-state smart --args '{"ScoreList":{}}'
+    archway query contract-state smart --args '{"ScoreList":{"game_id": "default"}}'
     ;;
   "admins")
     archway query contract-state smart --args '{"AdminsList":{}}'
     ;;
   "total-game-played")
-    archway query contract-state smart --args '{"GameCounter":{}}'
+    archway query contract-state smart --args '{"GameCounter":{"game_id": "default"}}'
     ;;
   "total-game-played-raw")
-    archwayd query wasm contract-state smart archway19cmtglphcfhrkyr3hd39dh598gl26vg9j6f5kp7y43k3879cscrs2tz6y4 '{"GameCounter":{}}' --node https://rpc.constantine.archway.tech:443
+    archwayd query wasm contract-state smart archway19cmtglphcfhrkyr3hd39dh598gl26vg9j6f5kp7y43k3879cscrs2tz6y4 '{"GameCounter":{"game_id": "default"}}' --node https://rpc.constantine.archway.tech:443
     ;;
   "store-user-record")
-    archway tx --args '{"AddTopUser": {"user": {"address":"archway1uwew6p8k70xa2lkzeujqcw430uky49zthsvc0y", "name":"Wotori", "score":27000}}}'
+    archway tx --args '{"AddTopUser": {"game_id": "default", "user": {"address":"archway1uwew6p8k70xa2lkzeujqcw430uky49zthsvc0y", "name":"Wotori", "score":"27000"}, "session_id": 1}}'
     ;;
   "store-user-record-raw")
-    archwayd tx wasm execute --chain-id constantine-3 --gas auto --gas-prices $(archwayd q rewards estimate-fees 1 --node 'https://rpc.constantine.archway.tech:443' --output json | jq -r '.gas_unit_price | (.amount + .denom)') --gas-adjustment 1.4 archway1tykvjvpvfqr5g7f8uqqg5du8tp0h99jcgvf05xumtgcq3vf5vajsvp9v2e  '{"AddTopUser": {"user": {"address":"archway1uwew6p8k70xa2lkzeujqcw430uky49zthsvc0y", "name":"Wotori", "score":27000}}}' --from wallet_name --node https://rpc.constantine.archway.tech:443 -y
+    archwayd tx wasm execute --chain-id constantine-3 --gas auto --gas-prices $(archwayd q rewards estimate-fees 1 --node 'https://rpc.constantine.archway.tech:443' --output json | jq -r '.gas_unit_price | (.amount + .denom)') --gas-adjustment 1.4 archway1tykvjvpvfqr5g7f8uqqg5du8tp0h99jcgvf05xumtgcq3vf5vajsvp9v2e  '{"AddTopUser": {"game_id": "default", "user": {"address":"archway1uwew6p8k70xa2lkzeujqcw430uky49zthsvc0y", "name":"Wotori", "score":"27000"}, "session_id": 1}}' --from wallet_name --node https://rpc.constantine.archway.tech:443 -y
     ;;
   "add-admin")
     archway tx --args '{"AddAdmin": {"admins": ["archway1uwew6p8k70xa2lkzeujqcw430uky49zthsvc0y"]}}'
@@ -1,35 +1,190 @@
 use crate::{
     error::ContractError,
-    msg::{ExecuteMsg, InstantiateMsg, QueryMsg},
+    migrations::{assign_roles, migrate_single_game, LEGACY_ARCADE},
+    msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg},
     state::{
-        User, ADMINS, ARCADE, ARCADE_DENOM, GAME_COUNTER, MAX_TOP_SCORES,
-        PRICE_PEER_GAME, TOP_USERS, TOTAL_PRICE_DISTRIBUTED,
+        AssetInfo, Feature, Game, GameStatus, Role, DEFAULT_GAME_ID, GAMES,
+        GAME_COUNTER, PENDING_TROPHIES, PRIZE_POOL, REVENUE_SPLIT, ROLES,
+        SCORE_SEQ, SESSION_COUNTER, SESSION_TTL, TOP_USERS,
+        TOTAL_PRICE_DISTRIBUTED, TROPHIES_MINTED,
+    },
+    utils::{
+        ensure_not_paused, ensure_role, grant_roles, or_none, start_season,
+        trophy_token_id, validate_game, validate_revenue_split, Funds,
     },
 };
 use cosmwasm_std::{
-    to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+    to_json_binary, to_json_string, Binary, BlockInfo, Deps, DepsMut, Env,
+    Event, MessageInfo, Reply, Response, StdError, StdResult, Storage,
+    SubMsgResult,
 };
+use cw2::{set_contract_version, CONTRACT};
+use semver::Version;
+
+const CONTRACT_NAME: &str = "crates.io:cw-arcade";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
+    env: Env,
+    info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     let admins: StdResult<Vec<_>> = msg
         .admins
         .into_iter()
         .map(|addr| deps.api.addr_validate(&addr))
         .collect();
-    ADMINS.save(deps.storage, &admins?)?;
-    ARCADE.save(deps.storage, &msg.arcade)?;
-    MAX_TOP_SCORES.save(deps.storage, &msg.max_top_score)?;
-    TOP_USERS.save(deps.storage, &Vec::<User>::new())?;
-    GAME_COUNTER.save(deps.storage, &0)?;
-    ARCADE_DENOM.save(deps.storage, &msg.denom)?;
-    PRICE_PEER_GAME.save(deps.storage, &msg.price_peer_game)?;
-    TOTAL_PRICE_DISTRIBUTED.save(deps.storage, &0)?;
-    Ok(Response::new())
+    let admins = admins?;
+    grant_roles(deps.storage, &info.sender, &[Role::Owner])?;
+    for admin in &admins {
+        grant_roles(deps.storage, admin, &Role::OPERATIONAL)?;
+    }
+    let game = Game {
+        name: msg.arcade,
+        asset: AssetInfo::Native(msg.denom),
+        price: msg.price_peer_game,
+        max_top_scores: msg.max_top_score,
+        entry_mode: msg.entry_mode,
+        score_order: msg.score_order,
+        status: GameStatus::Active,
+        payout_schedule: None,
+        season_duration: None,
+    };
+    validate_game(deps.storage, &game)?;
+    save_new_game(deps.storage, &env.block, DEFAULT_GAME_ID, &game)?;
+    SESSION_COUNTER.save(deps.storage, &0)?;
+    SCORE_SEQ.save(deps.storage, &0)?;
+    SESSION_TTL.save(deps.storage, &msg.session_ttl)?;
+    validate_revenue_split(deps.api, &msg.revenue_split)?;
+    REVENUE_SPLIT.save(deps.storage, &msg.revenue_split)?;
+    let mut resp = Response::new();
+    for admin in &admins {
+        resp = resp.add_event(
+            Event::new("arcade_admin_added").add_attribute("admin", admin),
+        );
+    }
+    Ok(resp.add_event(game_added_event(DEFAULT_GAME_ID, &game)))
+}
+
+pub fn migrate(
+    mut deps: DepsMut,
+    env: Env,
+    msg: MigrateMsg,
+) -> Result<Response, ContractError> {
+    let from_version = match CONTRACT.may_load(deps.storage)? {
+        Some(stored) => {
+            if stored.contract != CONTRACT_NAME {
+                return Err(ContractError::WrongContract {
+                    contract: stored.contract,
+                });
+            }
+            let from = parse_version(&stored.version)?;
+            if from > parse_version(CONTRACT_VERSION)? {
+                return Err(ContractError::MigrationDowngrade {
+                    from: stored.version,
+                    to: CONTRACT_VERSION.to_string(),
+                });
+            }
+            stored.version
+        }
+        // contracts instantiated before versioning have the single game
+        // layout of the first release
+        None if LEGACY_ARCADE.exists(deps.storage) => {
+            migrate_single_game(deps.branch(), &env, &msg)?;
+            "1.0.0".to_string()
+        }
+        None => return Err(ContractError::UnknownLayout),
+    };
+    if ROLES.is_empty(deps.storage) {
+        assign_roles(deps.branch(), &msg)?;
+    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", from_version.clone())
+        .add_attribute("to_version", CONTRACT_VERSION)
+        .add_event(
+            Event::new("arcade_migrated")
+                .add_attribute("from_version", from_version)
+                .add_attribute("to_version", CONTRACT_VERSION),
+        ))
+}
+
+// only trophy mints reply, with the trophy number as id. a failed mint is
+// logged and the achievement that minted it still goes through
+pub fn reply(
+    deps: DepsMut,
+    _env: Env,
+    msg: Reply,
+) -> Result<Response, ContractError> {
+    let token_id = trophy_token_id(msg.id);
+    let pending = PENDING_TROPHIES.load(deps.storage, msg.id)?;
+    PENDING_TROPHIES.remove(deps.storage, msg.id);
+    match msg.result {
+        SubMsgResult::Ok(_) => {
+            let minted =
+                TROPHIES_MINTED.may_load(deps.storage)?.unwrap_or_default();
+            TROPHIES_MINTED.save(deps.storage, &(minted + 1))?;
+            let metadata = pending.metadata;
+            Ok(Response::new()
+                .add_attribute("trophy", &token_id)
+                .add_event(
+                    Event::new("arcade_trophy_minted")
+                        .add_attribute("token_id", token_id)
+                        .add_attribute("owner", pending.owner)
+                        .add_attribute("kind", format!("{:?}", metadata.kind))
+                        .add_attribute("game_id", metadata.game_id)
+                        .add_attribute(
+                            "season_id",
+                            metadata.season_id.to_string(),
+                        )
+                        .add_attribute("score", metadata.score.to_string())
+                        .add_attribute("rank", metadata.rank.to_string()),
+                ))
+        }
+        SubMsgResult::Err(err) => Ok(Response::new()
+            .add_attribute("action", "trophy_mint_failed")
+            .add_attribute("token_id", &token_id)
+            .add_event(
+                Event::new("arcade_trophy_mint_failed")
+                    .add_attribute("token_id", token_id)
+                    .add_attribute("owner", pending.owner)
+                    .add_attribute("error", err),
+            )),
+    }
+}
+
+fn parse_version(version: &str) -> StdResult<Version> {
+    Version::parse(version)
+        .map_err(|err| StdError::generic_err(format!("{version}: {err}")))
+}
+
+fn game_added_event(game_id: &str, game: &Game) -> Event {
+    Event::new("arcade_game_added")
+        .add_attribute("game_id", game_id)
+        .add_attribute("name", &game.name)
+        .add_attribute("asset", game.asset.to_string())
+        .add_attribute("price", game.price.to_string())
+        .add_attribute("max_top_score", game.max_top_scores.to_string())
+        .add_attribute("entry_mode", format!("{:?}", game.entry_mode))
+        .add_attribute("score_order", format!("{:?}", game.score_order))
+}
+
+pub(crate) fn save_new_game(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    game_id: &str,
+    game: &Game,
+) -> StdResult<()> {
+    GAMES.save(storage, game_id, game)?;
+    TOP_USERS.save(storage, game_id, &vec![])?;
+    GAME_COUNTER.save(storage, game_id, &0)?;
+    PRIZE_POOL.save(storage, game_id, &0)?;
+    TOTAL_PRICE_DISTRIBUTED.save(storage, game_id, &0)?;
+    start_season(storage, block, game_id, 1)?;
+    Ok(())
 }
 
 pub fn execute(
@@ -40,627 +195,6318 @@ pub fn execute(
 ) -> Result<Response, ContractError> {
     use ExecuteMsg::*;
 
+    if let Some(role) = required_role(&msg) {
+        ensure_role(deps.storage, &info.sender, role)?;
+    }
+    if let Some(feature) = pausable_feature(&msg) {
+        ensure_not_paused(deps.storage, feature)?;
+    }
     match msg {
-        AddAdmin { admins } => exec::add_members(deps, info, admins),
-        AddTopUser { user } => exec::add_user(deps, info, user, env),
+        AddAdmin { admins } => exec::add_members(deps, admins),
+        AddTopUser {
+            game_id,
+            user,
+            session_id,
+        } => exec::add_user(deps, game_id, user, session_id, env),
         Leave {} => exec::leave(deps, info),
-        Play {} => exec::play(deps, info, env),
-        UpdatePrice { price } => exec::update_price(deps, price),
+        RemoveAdmin { admin } => exec::remove_admin(deps, admin),
+        ProposeOwner { owner } => exec::propose_owner(deps, owner),
+        AcceptOwnership {} => exec::accept_ownership(deps, info),
+        Pause { features, reason } => exec::pause(deps, env, features, reason),
+        Unpause { features } => exec::unpause(deps, features),
+        ClaimHeldPayouts {} => exec::claim_held_payouts(deps, info),
+        Play { game_id, token_id } => exec::play(
+            deps,
+            env,
+            info.sender,
+            game_id,
+            token_id,
+            Funds::Native(info.funds),
+        ),
+        Receive(wrapper) => exec::receive(deps, env, info, wrapper),
+        BuyCredits { game_id, count } => exec::buy_credits(
+            deps,
+            info.sender,
+            game_id,
+            count,
+            Funds::Native(info.funds),
+        ),
+        RefundCredits { game_id, address } => {
+            exec::refund_credits(deps, game_id, address)
+        }
+        SetAssetPrice {
+            game_id,
+            asset,
+            price,
+        } => exec::set_asset_price(deps, game_id, asset, price),
+        SetNftPass { game_id, pass } => exec::set_nft_pass(deps, game_id, pass),
+        SetBulkDiscounts { game_id, discounts } => {
+            exec::set_bulk_discounts(deps, game_id, discounts)
+        }
+        UpdatePrice { game_id, price } => {
+            exec::update_price(deps, game_id, price)
+        }
+        UpdateConfig {
+            game_id,
+            name,
+            asset,
+            price,
+            max_top_score,
+        } => exec::update_config(
+            deps,
+            game_id,
+            name,
+            asset,
+            price,
+            max_top_score,
+        ),
+        SetPriceBounds { bounds } => exec::set_price_bounds(deps, bounds),
+        SetPaymentMode { mode } => exec::set_payment_mode(deps, mode),
+        UpdateSessionTtl { blocks } => exec::update_session_ttl(deps, blocks),
+        RegisterServerKey {
+            name,
+            algorithm,
+            public_key,
+        } => exec::register_server_key(deps, name, algorithm, public_key),
+        RemoveServerKey { name } => exec::remove_server_key(deps, name),
+        SubmitScore {
+            game_id,
+            name,
+            score,
+            nonce,
+            server,
+            signature,
+            session_id,
+        } => exec::submit_score(
+            deps, info, env, game_id, name, score, nonce, server, signature,
+            session_id,
+        ),
+        AddGame {
+            game_id,
+            name,
+            asset,
+            price,
+            max_top_score,
+            entry_mode,
+            score_order,
+        } => exec::add_game(
+            deps,
+            env,
+            game_id,
+            Game {
+                name,
+                asset,
+                price,
+                max_top_scores: max_top_score,
+                entry_mode,
+                score_order,
+                status: GameStatus::Active,
+                payout_schedule: None,
+                season_duration: None,
+            },
+        ),
+        PauseGame { game_id } => {
+            exec::set_game_status(deps, game_id, GameStatus::Paused)
+        }
+        ResumeGame { game_id } => {
+            exec::set_game_status(deps, game_id, GameStatus::Active)
+        }
+        RetireGame { game_id } => {
+            exec::set_game_status(deps, game_id, GameStatus::Retired)
+        }
+        FundPrizePool { game_id } => exec::fund_prize_pool(
+            deps,
+            info.sender,
+            game_id,
+            Funds::Native(info.funds),
+        ),
+        SweepSurplus { denom, recipient } => {
+            exec::sweep_surplus(deps, env, denom, recipient)
+        }
+        UpdateRevenueSplit { revenue_split } => {
+            exec::update_revenue_split(deps, revenue_split)
+        }
+        SetPayoutSchedule { game_id, schedule } => {
+            exec::set_payout_schedule(deps, game_id, schedule)
+        }
+        Distribute { game_id } => exec::distribute(deps, game_id),
+        SetSeasonDuration { game_id, duration } => {
+            exec::set_season_duration(deps, game_id, duration)
+        }
+        EndSeason { game_id } => exec::end_season(deps, env, game_id),
+        SetTrophyConfig { config } => exec::set_trophy_config(deps, config),
+        GrantRole { address, role } => exec::grant_role(deps, address, role),
+        RevokeRole { address, role } => exec::revoke_role(deps, address, role),
     }
 }
 
-mod exec {
-    use std::collections::BinaryHeap;
+// the role a message needs, checked before it is handled. `None` for the
+// player facing messages
+fn required_role(msg: &ExecuteMsg) -> Option<Role> {
+    use ExecuteMsg::*;
+
+    match msg {
+        // accepting is checked against the pending owner
+        Play { .. }
+        | Receive(_)
+        | BuyCredits { .. }
+        | SubmitScore { .. }
+        | FundPrizePool { .. }
+        | Leave {}
+        | AcceptOwnership {}
+        | ClaimHeldPayouts {} => None,
+        AddAdmin { .. }
+        | RemoveAdmin { .. }
+        | ProposeOwner { .. }
+        | GrantRole { .. }
+        | RevokeRole { .. }
+        | SetPriceBounds { .. } => Some(Role::Owner),
+        UpdatePrice { .. }
+        | UpdateConfig { .. }
+        | SetPaymentMode { .. }
+        | SetAssetPrice { .. }
+        | SetBulkDiscounts { .. }
+        | SetNftPass { .. }
+        | UpdateSessionTtl { .. }
+        | RegisterServerKey { .. }
+        | RemoveServerKey { .. }
+        | AddGame { .. }
+        | RetireGame { .. }
+        | SetSeasonDuration { .. }
+        | EndSeason { .. }
+        | SetTrophyConfig { .. } => Some(Role::ConfigManager),
+        AddTopUser { .. } => Some(Role::ScoreReporter),
+        SweepSurplus { .. }
+        | RefundCredits { .. }
+        | UpdateRevenueSplit { .. }
+        | SetPayoutSchedule { .. }
+        | Distribute { .. } => Some(Role::Treasurer),
+        PauseGame { .. }
+        | ResumeGame { .. }
+        | Pause { .. }
+        | Unpause { .. } => Some(Role::Pauser),
+    }
+}
+
+// the feature a message is stopped with while it is paused
+fn pausable_feature(msg: &ExecuteMsg) -> Option<Feature> {
+    use ExecuteMsg::*;
+
+    match msg {
+        // the hooks of `Receive` are checked when they are handled
+        Play { .. } | BuyCredits { .. } => Some(Feature::Play),
+        AddTopUser { .. } | SubmitScore { .. } => {
+            Some(Feature::ScoreSubmission)
+        }
+        SweepSurplus { .. } | ClaimHeldPayouts {} | RefundCredits { .. } => {
+            Some(Feature::Payouts)
+        }
+        AddAdmin { .. }
+        | RemoveAdmin { .. }
+        | Leave {}
+        | GrantRole { .. }
+        | RevokeRole { .. }
+        | ProposeOwner { .. }
+        | AcceptOwnership {} => Some(Feature::AdminChanges),
+        _ => None,
+    }
+}
 
-    use cosmwasm_std::{coins, BankMsg};
+mod exec {
+    use cosmwasm_std::{
+        coins, from_json, Addr, BankMsg, Empty, Order, Uint128, Uint64,
+    };
+    use cw20::Cw20ReceiveMsg;
 
     use super::*;
+    use crate::utils::{
+        add_to_prize_pool, admins, advance_season, apply_discount, burn_msg,
+        credits_cost, distribute_prizes, has_role, is_admin, load_game,
+        mint_trophy, must_pay_asset, payment_asset, ranked_scoreboard,
+        ranks_above, revoke_roles, role_holders, send_coins, session_expired,
+        spend_credit, split_revenue, take_payment, transfer_msg,
+        unaccounted_surplus, update_player_stats, use_nft_pass, validate_asset,
+        validate_bulk_discounts, validate_nft_pass, validate_payout_schedule,
+        Payment,
+    };
     use crate::{
         error::ContractError,
-        state::{User, TOP_USERS},
-        utils::{user_is_top},
+        msg::{ReceiveMsg, ScoreAttestation},
+        state::{
+            AssetInfo, BulkDiscount, EntryMode, KeyAlgorithm, NftPass,
+            PauseInfo, PaymentMode, PriceBounds, RevenueSplit, ScoreEntry,
+            ScoreOrder, SeasonDuration, ServerKey, Session, TrophyConfig,
+            TrophyKind, TrophyMetadata, User, ACCEPTED_ASSETS, ASSET_POOLS,
+            BOARD_ENTRANTS, BULK_DISCOUNTS, CREDITS, CREDIT_ESCROW,
+            CURRENT_SEASON, HELD_PAYOUTS, NFT_PASSES, PAUSED, PAYMENT_MODE,
+            PENDING_OWNER, PLAYER_SESSIONS, PRICE_BOUNDS, SCORE_SEQ,
+            SERVER_KEYS, SESSIONS, TROPHY_CONFIG, USED_NONCES,
+        },
+        utils::{user_is_top, validate_public_key, verify_attestation},
     };
-    use crate::utils::send_coins;
 
     pub fn add_user(
         mut deps: DepsMut,
-        info: MessageInfo,
+        game_id: String,
         user: User,
+        session_id: u64,
+        env: Env,
+    ) -> Result<Response, ContractError> {
+        close_session(
+            deps.branch(),
+            &env,
+            &game_id,
+            session_id,
+            &user.address,
+        )?;
+        insert_score(deps, &env, &game_id, session_id, user)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn submit_score(
+        mut deps: DepsMut,
+        info: MessageInfo,
         env: Env,
+        game_id: String,
+        name: String,
+        score: Uint64,
+        nonce: u64,
+        server: String,
+        signature: Binary,
+        session_id: u64,
+    ) -> Result<Response, ContractError> {
+        let key = SERVER_KEYS
+            .may_load(deps.storage, &server)?
+            .ok_or(ContractError::UnknownServerKey { name: server })?;
+        if USED_NONCES.has(deps.storage, (&info.sender, nonce)) {
+            return Err(ContractError::NonceUsed { nonce });
+        }
+
+        let attestation = ScoreAttestation {
+            contract: env.contract.address.to_string(),
+            game_id: game_id.clone(),
+            player: info.sender.to_string(),
+            score,
+            nonce,
+        };
+        verify_attestation(deps.api, &key, &attestation, &signature)?;
+        USED_NONCES.save(deps.storage, (&info.sender, nonce), &Empty {})?;
+        close_session(deps.branch(), &env, &game_id, session_id, &info.sender)?;
+
+        let user = User {
+            name,
+            address: info.sender,
+            score,
+        };
+        insert_score(deps, &env, &game_id, session_id, user)
+    }
+
+    fn close_session(
+        deps: DepsMut,
+        env: &Env,
+        game_id: &str,
+        id: u64,
+        player: &Addr,
+    ) -> Result<(), ContractError> {
+        if load_game(deps.storage, game_id)?.status == GameStatus::Retired {
+            return Err(ContractError::GameRetired {
+                game_id: game_id.to_string(),
+            });
+        }
+        let mut session = SESSIONS
+            .may_load(deps.storage, id)?
+            .ok_or(ContractError::SessionNotFound { id })?;
+        if session.game_id != game_id {
+            return Err(ContractError::SessionGameMismatch {
+                id,
+                game_id: game_id.to_string(),
+            });
+        }
+        if session.player != *player {
+            return Err(ContractError::SessionPlayerMismatch {
+                id,
+                player: player.clone(),
+            });
+        }
+        if session.closed {
+            return Err(ContractError::SessionClosed { id });
+        }
+        let ttl = SESSION_TTL.load(deps.storage)?;
+        if session_expired(&session, ttl, env) {
+            return Err(ContractError::SessionExpired { id });
+        }
+        session.closed = true;
+        SESSIONS.save(deps.storage, id, &session)?;
+        Ok(())
+    }
+
+    fn insert_score(
+        mut deps: DepsMut,
+        env: &Env,
+        game_id: &str,
+        session_id: u64,
+        user: User,
     ) -> Result<Response, ContractError> {
-        let admins = ADMINS.load(deps.storage)?;
-        if admins.contains(&info.sender) {
-            let mut resp = Response::new();
-            let max = MAX_TOP_SCORES.load(deps.storage)?;
-            let cur_top_users = TOP_USERS.load(deps.storage)?;
-            let mut heap = BinaryHeap::from(cur_top_users);
-            if heap.len() < max.into() {
-                heap.push(user);
-            } else if let Some(lowest_score_user) = heap.peek() {
-                if lowest_score_user.score > user.score {
-                    // check if user top score for send prize pool to his account
-                    if user_is_top(&heap, &user) {
-                        // send all accumulated coins to the winner.
-                        resp = send_coins(&mut deps, &user, env)?;
-                    }
-
-                    // adding user to top score list // > used here because the lower the value, the greater it is
-                    heap.pop();
-                    heap.push(user);
+        let mut resp = advance_season(&mut deps, env, game_id)?;
+        let game = load_game(deps.storage, game_id)?;
+        // every accepted score counts, even the ones that miss the board
+        let order = game.score_order.clone();
+        update_player_stats(deps.storage, game_id, &user.address, |stats| {
+            stats.personal_best = match (&order, stats.personal_best) {
+                (ScoreOrder::HigherIsBetter, Some(best)) => {
+                    Some(best.max(user.score))
+                }
+                (ScoreOrder::LowerIsBetter, Some(best)) => {
+                    Some(best.min(user.score))
+                }
+                (_, None) => Some(user.score),
+            };
+            stats.last_played = Some(env.block.time);
+        })?;
+        let seq = SCORE_SEQ.load(deps.storage)? + 1;
+        SCORE_SEQ.save(deps.storage, &seq)?;
+        let entry = ScoreEntry {
+            user,
+            height: env.block.height,
+            time: env.block.time,
+            seq,
+        };
+        // kept in rank order, best score first
+        let mut scoreboard = ranked_scoreboard(deps.storage, game_id)?;
+        let previous = match game.entry_mode {
+            EntryMode::OnePerAddress => scoreboard
+                .iter()
+                .position(|top| top.user.address == entry.user.address),
+            EntryMode::Multiple => None,
+        };
+        let board_full = scoreboard.len() >= game.max_top_scores.into();
+        // an equal score does not push out the earlier submission
+        let enters = match previous {
+            Some(previous) => {
+                ranks_above(&order, &entry, &scoreboard[previous])
+            }
+            None => {
+                !board_full
+                    || scoreboard.last().is_some_and(|lowest| {
+                        ranks_above(&order, &entry, lowest)
+                    })
+            }
+        };
+        let season = CURRENT_SEASON.load(deps.storage, game_id)?;
+        let submitted = Event::new("arcade_score_submitted")
+            .add_attribute("game_id", game_id)
+            .add_attribute("season_id", season.id.to_string())
+            .add_attribute("session_id", session_id.to_string())
+            .add_attribute("player", entry.user.address.clone())
+            .add_attribute("name", entry.user.name.clone())
+            .add_attribute("score", entry.user.score.to_string());
+        let mut entered_rank = None;
+        if enters {
+            // check if user top score for send prize pool to his account,
+            // games with a payout schedule pay out on `Distribute`
+            if board_full
+                && game.payout_schedule.is_none()
+                && user_is_top(&order, &scoreboard, &entry)
+            {
+                // send all accumulated coins to the winner.
+                let payout =
+                    send_coins(&mut deps, game_id, season.id, &entry.user)?;
+                resp = resp
+                    .add_submessages(payout.messages)
+                    .add_attributes(payout.attributes)
+                    .add_events(payout.events);
+            }
+
+            let player = entry.user.address.clone();
+            let was_top = scoreboard
+                .first()
+                .is_some_and(|top| top.user.address == player);
+            // adding user to top score list, in place of their previous
+            // entry or of the lowest score of a full board
+            match previous {
+                Some(previous) => {
+                    scoreboard.remove(previous);
                 }
+                None if board_full => {
+                    scoreboard.pop();
+                }
+                None => {}
+            }
+            let rank = scoreboard
+                .partition_point(|top| ranks_above(&order, top, &entry));
+            entered_rank = Some(rank + 1);
+            if rank == 0 {
+                let previous_top = scoreboard.first();
+                resp = resp.add_event(
+                    Event::new("arcade_new_high_score")
+                        .add_attribute("game_id", game_id)
+                        .add_attribute("season_id", season.id.to_string())
+                        .add_attribute("player", player.clone())
+                        .add_attribute("score", entry.user.score.to_string())
+                        .add_attribute(
+                            "previous_player",
+                            or_none(previous_top.map(|top| &top.user.address)),
+                        )
+                        .add_attribute(
+                            "previous_score",
+                            or_none(previous_top.map(|top| top.user.score)),
+                        ),
+                );
             }
 
-            let vec = heap.into_vec();
-            TOP_USERS.save(deps.storage, &vec)?;
-            Ok(resp)
-        } else {
-            Err(ContractError::Unauthorized {
-                sender: (info.sender),
-            })
+            let mut trophies = vec![];
+            if !BOARD_ENTRANTS.has(deps.storage, (game_id, &player)) {
+                BOARD_ENTRANTS.save(
+                    deps.storage,
+                    (game_id, &player),
+                    &Empty {},
+                )?;
+                trophies.push(TrophyKind::FirstEntry);
+            }
+            if rank == 0 && !was_top {
+                trophies.push(TrophyKind::TopRank);
+            }
+            for kind in trophies {
+                let metadata = TrophyMetadata {
+                    kind,
+                    game_id: game_id.to_string(),
+                    score: entry.user.score,
+                    rank: rank as u32 + 1,
+                    season_id: season.id,
+                    time: env.block.time,
+                };
+                resp = mint_trophy(deps.storage, resp, &player, metadata)?;
+            }
+            scoreboard.insert(rank, entry);
         }
+
+        TOP_USERS.save(deps.storage, game_id, &scoreboard)?;
+        Ok(resp
+            .add_attribute("season_id", season.id.to_string())
+            .add_event(submitted.add_attribute("rank", or_none(entered_rank))))
     }
 
     pub fn add_members(
         deps: DepsMut,
-        info: MessageInfo,
         admins: Vec<String>,
     ) -> Result<Response, ContractError> {
-        let mut curr_admins = ADMINS.load(deps.storage)?;
-        if !curr_admins.contains(&info.sender) {
-            return Err(ContractError::Unauthorized {
-                sender: info.sender,
-            });
+        let mut resp = Response::new();
+        for admin in admins {
+            let admin = deps.api.addr_validate(&admin)?;
+            if Role::OPERATIONAL
+                .into_iter()
+                .all(|role| has_role(deps.storage, &admin, role))
+            {
+                continue;
+            }
+            grant_roles(deps.storage, &admin, &Role::OPERATIONAL)?;
+            resp = resp.add_event(
+                Event::new("arcade_admin_added").add_attribute("admin", &admin),
+            );
         }
-        let admins: StdResult<Vec<_>> = admins
-            .into_iter()
-            .map(|addr| deps.api.addr_validate(&addr))
-            .collect();
-        curr_admins.append(&mut admins?);
-        ADMINS.save(deps.storage, &curr_admins)?;
-        Ok(Response::new())
+        Ok(resp)
     }
 
-    pub fn leave(
+    pub fn remove_admin(
         deps: DepsMut,
-        info: MessageInfo,
+        admin: String,
     ) -> Result<Response, ContractError> {
-        ADMINS.update(deps.storage, move |admins| -> StdResult<_> {
-            let admins = admins
-                .into_iter()
-                .filter(|admin| *admin != info.sender)
-                .collect();
-            Ok(admins)
-        })?;
-
-        Ok(Response::new())
+        let admin = deps.api.addr_validate(&admin)?;
+        remove_from_admins(deps.storage, &admin)?;
+        Ok(Response::new()
+            .add_attribute("action", "remove_admin")
+            .add_attribute("admin", admin.clone())
+            .add_event(admin_removed_event(&admin)))
     }
 
-    pub fn play(
+    pub fn leave(
         deps: DepsMut,
         info: MessageInfo,
-        env: Env,
     ) -> Result<Response, ContractError> {
-        let price_peer_game = PRICE_PEER_GAME.load(deps.storage)?;
-        //transfer token to admins
-        let denom = ARCADE_DENOM.load(deps.storage)?;
-        let mut admins = ADMINS.load(deps.storage)?;
-        let self_address = env.contract.address;
-        admins.push(self_address.clone());
-        let tokens = cw_utils::must_pay(&info, &denom)?.u128();
-        // TODO: check if tokens equal minimum amount (the price should cover next admin transaction)
-        if tokens >= price_peer_game {
-            let tokens_peer_admin = tokens / (admins.len() as u128);
-            let left_tokens = tokens % (admins.len() as u128);
-            let messages = admins.into_iter().map(|admin| BankMsg::Send {
-                // this send is works as expected and testd
-                to_address: admin.to_string(),
-                amount: coins(tokens_peer_admin, &denom),
-            });
-            if left_tokens > 0 {
-                // send tokens to arcade contract
-                // TODO: check if it really works
-                BankMsg::Send {
-                    to_address: self_address.to_string(),
-                    amount: coins(tokens_peer_admin, &denom),
-                };
-            }
+        remove_from_admins(deps.storage, &info.sender)?;
+        Ok(Response::new().add_event(admin_removed_event(&info.sender)))
+    }
 
-            // increment game counter
-            let mut counter = GAME_COUNTER.load(deps.storage)?;
-            counter += 1;
-            GAME_COUNTER.save(deps.storage, &counter)?;
+    fn admin_removed_event(admin: &Addr) -> Event {
+        Event::new("arcade_admin_removed").add_attribute("admin", admin)
+    }
 
-            Ok(Response::new()
-                .add_messages(messages)
-                .add_attribute("recieved_tokens", tokens.to_string()))
-        } else {
-            // return coins to sender
-            // TODO: need to be tested (not sure if it works) // potentially it is not even need to be here
-            BankMsg::Send {
-                to_address: info.sender.to_string(),
-                amount: coins(tokens, &denom),
-            };
-            Err(ContractError::Unauthorized {
-                sender: info.sender,
-            })
+    // the admin list must not become empty, nobody could report scores
+    fn remove_from_admins(
+        storage: &mut dyn Storage,
+        admin: &Addr,
+    ) -> Result<(), ContractError> {
+        if !is_admin(storage, admin) {
+            return Err(ContractError::NotAdmin {
+                address: admin.clone(),
+            });
         }
+        revoke_roles(storage, admin, &Role::OPERATIONAL);
+        ensure_admins_left(storage)
     }
 
-    pub fn update_price(
+    fn ensure_admins_left(storage: &dyn Storage) -> Result<(), ContractError> {
+        if admins(storage)?.is_empty() {
+            return Err(ContractError::LastAdmin);
+        }
+        Ok(())
+    }
+
+    pub fn grant_role(
         deps: DepsMut,
-        price: u128,
+        address: String,
+        role: Role,
     ) -> Result<Response, ContractError> {
-        PRICE_PEER_GAME.save(deps.storage, &price)?;
-        Ok(Response::new())
+        if role == Role::Owner {
+            return Err(ContractError::OwnerRole);
+        }
+        let address = deps.api.addr_validate(&address)?;
+        grant_roles(deps.storage, &address, &[role])?;
+        Ok(Response::new()
+            .add_attribute("action", "grant_role")
+            .add_attribute("address", address.clone())
+            .add_attribute("role", role.as_str())
+            .add_event(
+                Event::new("arcade_role_granted")
+                    .add_attribute("address", address)
+                    .add_attribute("role", role.as_str()),
+            ))
     }
-}
 
-pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    use QueryMsg::*;
-
-    match msg {
-        AdminsList {} => to_binary(&query::admins_list(deps)?),
-        ScoreList {} => to_binary(&query::scoreboard(deps)?),
-        GameCounter {} => to_binary(&query::game_counter(deps)?),
-        Price {} => to_binary(&query::get_price(deps)?),
-        PrizePool {} => to_binary(&query::prize_pool(deps, env)?),
-        TotalDistributed {} => to_binary(&query::total_distributed(deps)?),
+    pub fn revoke_role(
+        deps: DepsMut,
+        address: String,
+        role: Role,
+    ) -> Result<Response, ContractError> {
+        if role == Role::Owner {
+            return Err(ContractError::OwnerRole);
+        }
+        let address = deps.api.addr_validate(&address)?;
+        let was_admin = is_admin(deps.storage, &address);
+        revoke_roles(deps.storage, &address, &[role]);
+        // the last operational role of the last admin stays
+        if was_admin && !is_admin(deps.storage, &address) {
+            ensure_admins_left(deps.storage)?;
+        }
+        Ok(Response::new()
+            .add_attribute("action", "revoke_role")
+            .add_attribute("address", address.clone())
+            .add_attribute("role", role.as_str())
+            .add_event(
+                Event::new("arcade_role_revoked")
+                    .add_attribute("address", address)
+                    .add_attribute("role", role.as_str()),
+            ))
     }
-}
 
-mod query {
-    use crate::msg::{PrizePoolResp, TotalDistributionResp};
-    use crate::{
-        msg::{
-            AdminsListResp, GameCounterResp, GamePriceResp, ScoreboardListResp,
-        },
-        state::TOP_USERS,
-    };
-    use cosmwasm_std::{BalanceResponse, BankQuery};
+    pub fn propose_owner(
+        deps: DepsMut,
+        owner: Option<String>,
+    ) -> Result<Response, ContractError> {
+        let previous = PENDING_OWNER.may_load(deps.storage)?;
+        let mut resp = Response::new().add_attribute("action", "propose_owner");
+        let owner = match owner {
+            Some(owner) => {
+                let owner = deps.api.addr_validate(&owner)?;
+                PENDING_OWNER.save(deps.storage, &owner)?;
+                resp = resp.add_attribute("pending_owner", owner.clone());
+                Some(owner)
+            }
+            None => {
+                PENDING_OWNER.remove(deps.storage);
+                None
+            }
+        };
+        Ok(resp.add_event(
+            Event::new("arcade_owner_proposed")
+                .add_attribute("previous_pending_owner", or_none(previous))
+                .add_attribute("pending_owner", or_none(owner)),
+        ))
+    }
 
-    use super::*;
+    pub fn accept_ownership(
+        deps: DepsMut,
+        info: MessageInfo,
+    ) -> Result<Response, ContractError> {
+        if PENDING_OWNER.may_load(deps.storage)?.as_ref() != Some(&info.sender)
+        {
+            return Err(ContractError::NotPendingOwner {
+                sender: info.sender,
+            });
+        }
+        let previous = role_holders(deps.storage, Role::Owner)?;
+        for owner in &previous {
+            revoke_roles(deps.storage, owner, &[Role::Owner]);
+        }
+        grant_roles(deps.storage, &info.sender, &[Role::Owner])?;
+        PENDING_OWNER.remove(deps.storage);
+        Ok(Response::new()
+            .add_attribute("action", "accept_ownership")
+            .add_attribute("owner", info.sender.clone())
+            .add_event(
+                Event::new("arcade_ownership_transferred")
+                    .add_attribute("previous_owner", or_none(previous.first()))
+                    .add_attribute("owner", info.sender),
+            ))
+    }
 
-    pub fn scoreboard(deps: Deps) -> StdResult<ScoreboardListResp> {
-        let scoreboard = TOP_USERS.load(deps.storage)?;
-        let resp = ScoreboardListResp { scores: scoreboard };
+    pub fn pause(
+        deps: DepsMut,
+        env: Env,
+        features: Vec<Feature>,
+        reason: String,
+    ) -> Result<Response, ContractError> {
+        let mut resp = Response::new()
+            .add_attribute("action", "pause")
+            .add_attribute("reason", reason.clone());
+        for feature in features {
+            let pause = PauseInfo {
+                feature,
+                reason: reason.clone(),
+                since: env.block.time,
+            };
+            PAUSED.save(deps.storage, feature.as_str(), &pause)?;
+            resp = resp.add_event(
+                Event::new("arcade_paused")
+                    .add_attribute("feature", feature.as_str())
+                    .add_attribute("reason", reason.clone()),
+            );
+        }
         Ok(resp)
     }
 
-    pub fn admins_list(deps: Deps) -> StdResult<AdminsListResp> {
-        let admins = ADMINS.load(deps.storage)?;
-        let resp = AdminsListResp { admins };
+    pub fn unpause(
+        deps: DepsMut,
+        features: Vec<Feature>,
+    ) -> Result<Response, ContractError> {
+        let mut resp = Response::new().add_attribute("action", "unpause");
+        for feature in features {
+            PAUSED.remove(deps.storage, feature.as_str());
+            resp = resp.add_event(
+                Event::new("arcade_unpaused")
+                    .add_attribute("feature", feature.as_str()),
+            );
+        }
         Ok(resp)
     }
 
-    pub fn game_counter(deps: Deps) -> StdResult<GameCounterResp> {
-        let counter = GAME_COUNTER.load(deps.storage)?;
-        let resp = GameCounterResp {
-            game_counter: counter,
-        };
-        Ok(resp)
+    pub fn claim_held_payouts(
+        deps: DepsMut,
+        info: MessageInfo,
+    ) -> Result<Response, ContractError> {
+        let held: Vec<_> = HELD_PAYOUTS
+            .prefix(&info.sender)
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<_>>()?;
+        if held.is_empty() {
+            return Err(ContractError::NoHeldPayouts {
+                recipient: info.sender,
+            });
+        }
+        let mut messages = vec![];
+        let mut events = vec![];
+        for (key, held_amount) in held {
+            HELD_PAYOUTS.remove(deps.storage, (&info.sender, &key));
+            let asset = AssetInfo::from_key(&key);
+            messages.push(transfer_msg(&asset, &info.sender, held_amount)?);
+            events.push(
+                Event::new("arcade_held_payout_claimed")
+                    .add_attribute("recipient", info.sender.clone())
+                    .add_attribute("asset", asset.to_string())
+                    .add_attribute("amount", held_amount.to_string()),
+            );
+        }
+        Ok(Response::new()
+            .add_messages(messages)
+            .add_events(events)
+            .add_attribute("action", "claim_held_payouts")
+            .add_attribute("recipient", info.sender))
     }
 
-    pub fn get_price(deps: Deps) -> StdResult<GamePriceResp> {
-        let price = PRICE_PEER_GAME.load(deps.storage)?;
-        let resp = GamePriceResp { price };
-        Ok(resp)
+    pub fn receive(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        wrapper: Cw20ReceiveMsg,
+    ) -> Result<Response, ContractError> {
+        let player = deps.api.addr_validate(&wrapper.sender)?;
+        // the sender of a `Receive` is the token contract
+        let funds = Funds::Cw20 {
+            token: info.sender,
+            amount: wrapper.amount.u128(),
+        };
+        match from_json(&wrapper.msg)? {
+            ReceiveMsg::Play { game_id, token_id } => {
+                ensure_not_paused(deps.storage, Feature::Play)?;
+                play(deps, env, player, game_id, token_id, funds)
+            }
+            ReceiveMsg::BuyCredits { game_id, count } => {
+                ensure_not_paused(deps.storage, Feature::Play)?;
+                buy_credits(deps, player, game_id, count, funds)
+            }
+            ReceiveMsg::FundPrizePool { game_id } => {
+                fund_prize_pool(deps, player, game_id, funds)
+            }
+        }
     }
 
-    pub fn prize_pool(deps: Deps, env: Env) -> StdResult<PrizePoolResp> {
-        // returns available amount of coins that will be distributed to the winner that hist the scoreboard
-        // TODO: replace with query_arcade_balance function (code duplicate)
-        let denom = ARCADE_DENOM.load(deps.storage)?;
-        let address = env.contract.address.to_string();
-        let balance_query = BankQuery::Balance { denom, address };
-        let balance_response: BalanceResponse =
-            deps.querier.query(&balance_query.into())?;
-        let balance_u128 = balance_response.amount.amount.u128();
-        Ok(PrizePoolResp {
-            prize_pool: balance_u128,
-        })
+    pub fn play(
+        mut deps: DepsMut,
+        env: Env,
+        player: Addr,
+        game_id: String,
+        token_id: Option<String>,
+        funds: Funds,
+    ) -> Result<Response, ContractError> {
+        let game = load_playable_game(deps.storage, game_id.clone())?;
+        let admins = admins(deps.storage)?;
+        let discount_bps = match &token_id {
+            Some(token_id) => Some(use_nft_pass(
+                &mut deps, &env, &game_id, &player, token_id,
+            )?),
+            None => None,
+        };
+        // a play without funds is paid with a credit when there is one,
+        // unless it is played with an NFT pass
+        let credit = if funds.is_empty() && token_id.is_none() {
+            spend_credit(deps.storage, &game_id, &player)?
+        } else {
+            None
+        };
+        let (asset, payment) = match credit {
+            Some(tokens) => (
+                game.asset.clone(),
+                Payment {
+                    tokens,
+                    change: vec![],
+                },
+            ),
+            None => {
+                let (asset, price) =
+                    payment_asset(deps.storage, &game_id, &game, &funds)?;
+                let price = match discount_bps {
+                    Some(discount_bps) => apply_discount(price, discount_bps),
+                    None => price,
+                };
+                let mode =
+                    PAYMENT_MODE.may_load(deps.storage)?.unwrap_or_default();
+                let payment = take_payment(&mode, &funds, &asset, price)?;
+                (asset, payment)
+            }
+        };
+        let tokens = payment.tokens;
+        let rollover = advance_season(&mut deps, &env, &game_id)?;
+        let season = CURRENT_SEASON.load(deps.storage, &game_id)?;
+        let split = REVENUE_SPLIT.load(deps.storage)?;
+        let shares = split_revenue(&split, tokens, admins.len());
+        let mut messages = vec![];
+        let mut send = |address: &Addr, amount: u128| -> StdResult<()> {
+            if amount > 0 {
+                messages.push(transfer_msg(&asset, address, amount)?);
+            }
+            Ok(())
+        };
+        if let Some(treasury) = &split.treasury {
+            send(&treasury.address, shares.treasury)?;
+        }
+        if let Some(developer) = &split.developer {
+            send(&developer.address, shares.developer)?;
+        }
+        for admin in &admins {
+            send(admin, shares.operators)?;
+        }
+        if shares.burn > 0 {
+            messages.push(burn_msg(&asset, shares.burn)?);
+        }
+        for change in &payment.change {
+            messages.push(transfer_msg(
+                &change.info,
+                &player,
+                change.amount.u128(),
+            )?);
+        }
+        // the prize pool share and the rounding dust stay in the arcade
+        // contract balance as the game prize pool
+        let prize = shares.prize_pool + shares.dust;
+        add_to_prize_pool(deps.storage, &game_id, &game, &asset, prize)?;
+
+        // spending is counted in the game asset, like winnings
+        let spent = if asset == game.asset { tokens } else { 0 };
+        update_player_stats(deps.storage, &game_id, &player, |stats| {
+            stats.games_played += 1;
+            stats.total_spent += spent;
+            stats.last_played = Some(env.block.time);
+        })?;
+
+        // increment game counter
+        let mut counter = GAME_COUNTER.load(deps.storage, &game_id)?;
+        counter += 1;
+        GAME_COUNTER.save(deps.storage, &game_id, &counter)?;
+
+        // open a session the score of this game will be submitted with
+        let session_id = SESSION_COUNTER.load(deps.storage)? + 1;
+        SESSION_COUNTER.save(deps.storage, &session_id)?;
+        let session = Session {
+            id: session_id,
+            game_id: game_id.clone(),
+            player: player.clone(),
+            height: env.block.height,
+            price_paid: tokens,
+            closed: false,
+        };
+        SESSIONS.save(deps.storage, session_id, &session)?;
+        PLAYER_SESSIONS.save(deps.storage, (&player, session_id), &Empty {})?;
+
+        let mut resp = rollover;
+        if !payment.change.is_empty() {
+            let change: Vec<_> =
+                payment.change.iter().map(ToString::to_string).collect();
+            resp = resp.add_attribute("change", change.join(","));
+        }
+        if let Some(token_id) = &token_id {
+            resp = resp.add_attribute("nft_pass", token_id);
+        }
+        let played = Event::new("arcade_game_played")
+            .add_attribute("game_id", &game_id)
+            .add_attribute("season_id", season.id.to_string())
+            .add_attribute("session_id", session_id.to_string())
+            .add_attribute("player", player)
+            .add_attribute("asset", asset.to_string())
+            .add_attribute("amount", tokens.to_string())
+            .add_attribute("prize_pool_share", prize.to_string())
+            .add_attribute("paid_with_credit", credit.is_some().to_string())
+            .add_attribute("nft_pass", or_none(token_id))
+            .add_attribute("game_counter", counter.to_string());
+        Ok(resp
+            .add_messages(messages)
+            .add_event(played)
+            .add_attribute("game_id", game_id)
+            .add_attribute("season_id", season.id.to_string())
+            .add_attribute("paid_with_credit", credit.is_some().to_string())
+            .add_attribute("asset", asset.to_string())
+            .add_attribute("recieved_tokens", tokens.to_string())
+            .add_attribute("prize_pool_share", shares.prize_pool.to_string())
+            .add_attribute("treasury_share", shares.treasury.to_string())
+            .add_attribute("developer_share", shares.developer.to_string())
+            .add_attribute("operator_share", shares.operators.to_string())
+            .add_attribute("burn_share", shares.burn.to_string())
+            .add_attribute("dust", shares.dust.to_string())
+            .add_attribute("session_id", session_id.to_string()))
     }
 
-    pub fn total_distributed(deps: Deps) -> StdResult<TotalDistributionResp> {
-        let total_distributed = TOTAL_PRICE_DISTRIBUTED.load(deps.storage)?;
-        Ok(TotalDistributionResp { total_distributed })
+    fn load_playable_game(
+        storage: &dyn Storage,
+        game_id: String,
+    ) -> Result<Game, ContractError> {
+        let game = load_game(storage, &game_id)?;
+        match game.status {
+            GameStatus::Active => Ok(game),
+            GameStatus::Paused => Err(ContractError::GamePaused { game_id }),
+            GameStatus::Retired => Err(ContractError::GameRetired { game_id }),
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::cmp::Reverse;
-    use super::*;
-    use crate::{
-        msg::{
-            AdminsListResp, GameCounterResp, InstantiateMsg, PrizePoolResp,
-            QueryMsg, ScoreboardListResp,
-        },
-        state::User,
-    };
-    use cosmwasm_std::{coins, Addr};
-    use cw_multi_test::{App, ContractWrapper, Executor};
-    use crate::msg::GamePriceResp;
+    pub fn buy_credits(
+        deps: DepsMut,
+        player: Addr,
+        game_id: String,
+        count: u32,
+        funds: Funds,
+    ) -> Result<Response, ContractError> {
+        if count == 0 {
+            return Err(ContractError::ZeroCredits);
+        }
+        let game = load_playable_game(deps.storage, game_id.clone())?;
+        let discounts = BULK_DISCOUNTS
+            .may_load(deps.storage, &game_id)?
+            .unwrap_or_default();
+        let cost = credits_cost(&discounts, game.price, count)?;
+        let mode = PAYMENT_MODE.may_load(deps.storage)?.unwrap_or_default();
+        let payment = take_payment(&mode, &funds, &game.asset, cost)?;
 
-    #[test]
-    fn play() {
-        let mut app = App::new(|router, _, storage| {
-            router
-                .bank
-                .init_balance(
+        let credits = CREDITS.update(
+            deps.storage,
+            (&game_id, &player),
+            |credits| -> Result<_, ContractError> {
+                let mut credits = credits.unwrap_or_default();
+                credits.count =
+                    credits.count.checked_add(count).ok_or_else(|| {
+                        ContractError::CreditsOverflow {
+                            address: player.clone(),
+                            game_id: game_id.clone(),
+                        }
+                    })?;
+                credits.paid = Uint128::new(credits.paid)
+                    .checked_add(Uint128::new(cost))
+                    .map_err(StdError::from)?
+                    .u128();
+                Ok(credits)
+            },
+        )?;
+        CREDIT_ESCROW.update(
+            deps.storage,
+            &game_id,
+            |escrow| -> StdResult<_> {
+                Ok(Uint128::new(escrow.unwrap_or_default())
+                    .checked_add(Uint128::new(cost))?
+                    .u128())
+            },
+        )?;
+
+        let mut resp = Response::new();
+        if !payment.change.is_empty() {
+            let change: Vec<_> =
+                payment.change.iter().map(ToString::to_string).collect();
+            resp = resp.add_attribute("change", change.join(","));
+        }
+        for change in &payment.change {
+            resp = resp.add_message(transfer_msg(
+                &change.info,
+                &player,
+                change.amount.u128(),
+            )?);
+        }
+        Ok(resp
+            .add_attribute("action", "buy_credits")
+            .add_attribute("game_id", &game_id)
+            .add_attribute("count", count.to_string())
+            .add_attribute("cost", cost.to_string())
+            .add_attribute("credits", credits.count.to_string())
+            .add_event(
+                Event::new("arcade_credits_bought")
+                    .add_attribute("game_id", game_id)
+                    .add_attribute("player", player)
+                    .add_attribute("asset", game.asset.to_string())
+                    .add_attribute("count", count.to_string())
+                    .add_attribute("amount", cost.to_string())
+                    .add_attribute("credits", credits.count.to_string()),
+            ))
+    }
+
+    pub fn refund_credits(
+        deps: DepsMut,
+        game_id: String,
+        address: String,
+    ) -> Result<Response, ContractError> {
+        let address = deps.api.addr_validate(&address)?;
+        let game = load_game(deps.storage, &game_id)?;
+        let credits = CREDITS
+            .may_load(deps.storage, (&game_id, &address))?
+            .ok_or_else(|| ContractError::NoCredits {
+                address: address.clone(),
+                game_id: game_id.clone(),
+            })?;
+        CREDITS.remove(deps.storage, (&game_id, &address));
+        CREDIT_ESCROW.update(
+            deps.storage,
+            &game_id,
+            |escrow| -> StdResult<_> {
+                Ok(escrow.unwrap_or_default() - credits.paid)
+            },
+        )?;
+        let mut resp = Response::new();
+        if credits.paid > 0 {
+            resp = resp.add_message(transfer_msg(
+                &game.asset,
+                &address,
+                credits.paid,
+            )?);
+        }
+        Ok(resp
+            .add_attribute("action", "refund_credits")
+            .add_attribute("game_id", &game_id)
+            .add_attribute("address", address.clone())
+            .add_attribute("count", credits.count.to_string())
+            .add_attribute("amount", credits.paid.to_string())
+            .add_event(
+                Event::new("arcade_credits_refunded")
+                    .add_attribute("game_id", game_id)
+                    .add_attribute("player", address)
+                    .add_attribute("asset", game.asset.to_string())
+                    .add_attribute("count", credits.count.to_string())
+                    .add_attribute("amount", credits.paid.to_string()),
+            ))
+    }
+
+    pub fn set_asset_price(
+        deps: DepsMut,
+        game_id: String,
+        asset: AssetInfo,
+        price: Option<u128>,
+    ) -> Result<Response, ContractError> {
+        let game = load_game(deps.storage, &game_id)?;
+        validate_asset(deps.api, &asset)?;
+        // the game asset is priced by `UpdatePrice`
+        if asset == game.asset {
+            return Err(ContractError::AssetAccepted {
+                game_id,
+                asset: asset.to_string(),
+            });
+        }
+        let key = asset.key();
+        let previous =
+            ACCEPTED_ASSETS.may_load(deps.storage, (&game_id, &key))?;
+        match price {
+            Some(price) => {
+                ACCEPTED_ASSETS.save(deps.storage, (&game_id, &key), &price)?
+            }
+            // its pool stays until it is paid out
+            None => ACCEPTED_ASSETS.remove(deps.storage, (&game_id, &key)),
+        }
+        Ok(Response::new().add_event(
+            Event::new("arcade_asset_price_updated")
+                .add_attribute("game_id", game_id)
+                .add_attribute("asset", asset.to_string())
+                .add_attribute("previous_price", or_none(previous))
+                .add_attribute("price", or_none(price)),
+        ))
+    }
+
+    pub fn set_bulk_discounts(
+        deps: DepsMut,
+        game_id: String,
+        discounts: Vec<BulkDiscount>,
+    ) -> Result<Response, ContractError> {
+        load_game(deps.storage, &game_id)?;
+        validate_bulk_discounts(&discounts)?;
+        let previous = BULK_DISCOUNTS
+            .may_load(deps.storage, &game_id)?
+            .unwrap_or_default();
+        if discounts.is_empty() {
+            BULK_DISCOUNTS.remove(deps.storage, &game_id);
+        } else {
+            BULK_DISCOUNTS.save(deps.storage, &game_id, &discounts)?;
+        }
+        Ok(Response::new().add_event(
+            Event::new("arcade_bulk_discounts_updated")
+                .add_attribute("game_id", game_id)
+                .add_attribute("previous_discounts", to_json_string(&previous)?)
+                .add_attribute("discounts", to_json_string(&discounts)?),
+        ))
+    }
+
+    pub fn set_nft_pass(
+        deps: DepsMut,
+        game_id: String,
+        pass: Option<NftPass>,
+    ) -> Result<Response, ContractError> {
+        load_game(deps.storage, &game_id)?;
+        let previous = NFT_PASSES.may_load(deps.storage, &game_id)?;
+        match &pass {
+            Some(pass) => {
+                validate_nft_pass(deps.api, pass)?;
+                NFT_PASSES.save(deps.storage, &game_id, pass)?;
+            }
+            None => NFT_PASSES.remove(deps.storage, &game_id),
+        }
+        Ok(Response::new().add_event(
+            Event::new("arcade_nft_pass_updated")
+                .add_attribute("game_id", game_id)
+                .add_attribute("previous_pass", to_json_string(&previous)?)
+                .add_attribute("pass", to_json_string(&pass)?),
+        ))
+    }
+
+    pub fn set_payment_mode(
+        deps: DepsMut,
+        mode: PaymentMode,
+    ) -> Result<Response, ContractError> {
+        let previous = PAYMENT_MODE.may_load(deps.storage)?.unwrap_or_default();
+        PAYMENT_MODE.save(deps.storage, &mode)?;
+        Ok(Response::new().add_event(
+            Event::new("arcade_payment_mode_updated")
+                .add_attribute("previous_mode", format!("{previous:?}"))
+                .add_attribute("mode", format!("{mode:?}")),
+        ))
+    }
+
+    pub fn update_price(
+        deps: DepsMut,
+        game_id: String,
+        price: u128,
+    ) -> Result<Response, ContractError> {
+        let mut game = load_game(deps.storage, &game_id)?;
+        let previous = game.price;
+        game.price = price;
+        validate_game(deps.storage, &game)?;
+        GAMES.save(deps.storage, &game_id, &game)?;
+        Ok(Response::new().add_event(
+            Event::new("arcade_price_updated")
+                .add_attribute("game_id", game_id)
+                .add_attribute("asset", game.asset.to_string())
+                .add_attribute("previous_price", previous.to_string())
+                .add_attribute("price", price.to_string()),
+        ))
+    }
+
+    pub fn update_config(
+        deps: DepsMut,
+        game_id: String,
+        name: Option<String>,
+        asset: Option<AssetInfo>,
+        price: Option<u128>,
+        max_top_score: Option<u8>,
+    ) -> Result<Response, ContractError> {
+        let mut game = load_game(deps.storage, &game_id)?;
+        let previous = game.clone();
+        if let Some(name) = name {
+            game.name = name;
+        }
+        if let Some(asset) = asset {
+            validate_asset(deps.api, &asset)?;
+            // the pool and the credit escrow are held in the old asset
+            if asset != game.asset {
+                let key = asset.key();
+                if ACCEPTED_ASSETS.has(deps.storage, (&game_id, &key))
+                    || ASSET_POOLS.has(deps.storage, (&game_id, &key))
+                {
+                    return Err(ContractError::AssetAccepted {
+                        game_id,
+                        asset: asset.to_string(),
+                    });
+                }
+                if PRIZE_POOL.load(deps.storage, &game_id)? > 0 {
+                    return Err(ContractError::PrizePoolNotEmpty { game_id });
+                }
+                if CREDIT_ESCROW
+                    .may_load(deps.storage, &game_id)?
+                    .unwrap_or_default()
+                    > 0
+                {
+                    return Err(ContractError::CreditsOutstanding { game_id });
+                }
+            }
+            game.asset = asset;
+        }
+        if let Some(price) = price {
+            game.price = price;
+        }
+        if let Some(max_top_score) = max_top_score {
+            game.max_top_scores = max_top_score;
+        }
+        validate_game(deps.storage, &game)?;
+        // a smaller board drops the lowest ranked entries
+        let mut scoreboard = ranked_scoreboard(deps.storage, &game_id)?;
+        scoreboard.truncate(game.max_top_scores.into());
+        TOP_USERS.save(deps.storage, &game_id, &scoreboard)?;
+        GAMES.save(deps.storage, &game_id, &game)?;
+        Ok(Response::new()
+            .add_attribute("action", "update_config")
+            .add_attribute("game_id", &game_id)
+            .add_event(
+                Event::new("arcade_config_updated")
+                    .add_attribute("game_id", game_id)
+                    .add_attribute("previous_name", previous.name)
+                    .add_attribute("name", game.name)
+                    .add_attribute("previous_asset", previous.asset.to_string())
+                    .add_attribute("asset", game.asset.to_string())
+                    .add_attribute("previous_price", previous.price.to_string())
+                    .add_attribute("price", game.price.to_string())
+                    .add_attribute(
+                        "previous_max_top_score",
+                        previous.max_top_scores.to_string(),
+                    )
+                    .add_attribute(
+                        "max_top_score",
+                        game.max_top_scores.to_string(),
+                    ),
+            ))
+    }
+
+    pub fn set_price_bounds(
+        deps: DepsMut,
+        bounds: Option<PriceBounds>,
+    ) -> Result<Response, ContractError> {
+        let previous = PRICE_BOUNDS.may_load(deps.storage)?;
+        match &bounds {
+            Some(bounds) => {
+                if bounds.min > bounds.max {
+                    return Err(ContractError::InvalidPriceBounds {
+                        min: bounds.min,
+                        max: bounds.max,
+                    });
+                }
+                PRICE_BOUNDS.save(deps.storage, bounds)?;
+            }
+            None => PRICE_BOUNDS.remove(deps.storage),
+        }
+        Ok(Response::new().add_event(
+            Event::new("arcade_price_bounds_updated")
+                .add_attribute("previous_bounds", to_json_string(&previous)?)
+                .add_attribute("bounds", to_json_string(&bounds)?),
+        ))
+    }
+
+    pub fn update_session_ttl(
+        deps: DepsMut,
+        blocks: u64,
+    ) -> Result<Response, ContractError> {
+        let previous = SESSION_TTL.load(deps.storage)?;
+        SESSION_TTL.save(deps.storage, &blocks)?;
+        Ok(Response::new().add_event(
+            Event::new("arcade_session_ttl_updated")
+                .add_attribute("previous_ttl", previous.to_string())
+                .add_attribute("ttl", blocks.to_string()),
+        ))
+    }
+
+    pub fn register_server_key(
+        deps: DepsMut,
+        name: String,
+        algorithm: KeyAlgorithm,
+        public_key: Binary,
+    ) -> Result<Response, ContractError> {
+        let key = ServerKey {
+            algorithm,
+            public_key,
+        };
+        validate_public_key(&key)?;
+        SERVER_KEYS.save(deps.storage, &name, &key)?;
+        Ok(Response::new().add_event(
+            Event::new("arcade_server_key_registered")
+                .add_attribute("name", name)
+                .add_attribute("algorithm", format!("{:?}", key.algorithm))
+                .add_attribute("public_key", key.public_key.to_base64()),
+        ))
+    }
+
+    pub fn remove_server_key(
+        deps: DepsMut,
+        name: String,
+    ) -> Result<Response, ContractError> {
+        SERVER_KEYS.remove(deps.storage, &name);
+        Ok(Response::new().add_event(
+            Event::new("arcade_server_key_removed").add_attribute("name", name),
+        ))
+    }
+
+    pub fn add_game(
+        deps: DepsMut,
+        env: Env,
+        game_id: String,
+        game: Game,
+    ) -> Result<Response, ContractError> {
+        if GAMES.has(deps.storage, &game_id) {
+            return Err(ContractError::GameExists { game_id });
+        }
+        validate_asset(deps.api, &game.asset)?;
+        validate_game(deps.storage, &game)?;
+        save_new_game(deps.storage, &env.block, &game_id, &game)?;
+        Ok(Response::new()
+            .add_attribute("game_id", &game_id)
+            .add_event(game_added_event(&game_id, &game)))
+    }
+
+    pub fn set_game_status(
+        deps: DepsMut,
+        game_id: String,
+        status: GameStatus,
+    ) -> Result<Response, ContractError> {
+        let mut game = load_game(deps.storage, &game_id)?;
+        // retiring a game is final
+        if game.status == GameStatus::Retired {
+            return Err(ContractError::GameRetired { game_id });
+        }
+        let previous = std::mem::replace(&mut game.status, status);
+        GAMES.save(deps.storage, &game_id, &game)?;
+        Ok(Response::new().add_event(
+            Event::new("arcade_game_status_updated")
+                .add_attribute("game_id", game_id)
+                .add_attribute("previous_status", format!("{previous:?}"))
+                .add_attribute("status", format!("{:?}", game.status)),
+        ))
+    }
+
+    pub fn fund_prize_pool(
+        deps: DepsMut,
+        sponsor: Addr,
+        game_id: String,
+        funds: Funds,
+    ) -> Result<Response, ContractError> {
+        let game = load_game(deps.storage, &game_id)?;
+        if game.status == GameStatus::Retired {
+            return Err(ContractError::GameRetired { game_id });
+        }
+        let (asset, _) = payment_asset(deps.storage, &game_id, &game, &funds)?;
+        let amount = must_pay_asset(&sponsor, funds, &asset)?;
+        let prize_pool =
+            add_to_prize_pool(deps.storage, &game_id, &game, &asset, amount)?;
+        let funded = Event::new("arcade_prize_pool_funded")
+            .add_attribute("game_id", &game_id)
+            .add_attribute("sponsor", sponsor.clone())
+            .add_attribute("asset", asset.to_string())
+            .add_attribute("amount", amount.to_string())
+            .add_attribute("prize_pool", prize_pool.to_string());
+        Ok(Response::new()
+            .add_attribute("action", "fund_prize_pool")
+            .add_attribute("game_id", game_id)
+            .add_attribute("asset", asset.to_string())
+            .add_attribute("sponsor", sponsor)
+            .add_attribute("amount", amount.to_string())
+            .add_attribute("prize_pool", prize_pool.to_string())
+            .add_event(funded))
+    }
+
+    pub fn sweep_surplus(
+        deps: DepsMut,
+        env: Env,
+        denom: String,
+        recipient: String,
+    ) -> Result<Response, ContractError> {
+        let recipient = deps.api.addr_validate(&recipient)?;
+        let amount = unaccounted_surplus(deps.as_ref(), &env, &denom)?;
+        if amount == 0 {
+            return Err(ContractError::NoFunds);
+        }
+        Ok(Response::new()
+            .add_message(BankMsg::Send {
+                to_address: recipient.to_string(),
+                amount: coins(amount, &denom),
+            })
+            .add_attribute("action", "sweep_surplus")
+            .add_attribute("recipient", recipient.clone())
+            .add_attribute("amount", amount.to_string())
+            .add_event(
+                Event::new("arcade_surplus_swept")
+                    .add_attribute("denom", denom)
+                    .add_attribute("recipient", recipient)
+                    .add_attribute("amount", amount.to_string()),
+            ))
+    }
+
+    pub fn update_revenue_split(
+        deps: DepsMut,
+        revenue_split: RevenueSplit,
+    ) -> Result<Response, ContractError> {
+        validate_revenue_split(deps.api, &revenue_split)?;
+        let previous = REVENUE_SPLIT.load(deps.storage)?;
+        REVENUE_SPLIT.save(deps.storage, &revenue_split)?;
+        Ok(Response::new().add_event(
+            Event::new("arcade_revenue_split_updated")
+                .add_attribute("previous_split", to_json_string(&previous)?)
+                .add_attribute("split", to_json_string(&revenue_split)?),
+        ))
+    }
+
+    pub fn set_payout_schedule(
+        deps: DepsMut,
+        game_id: String,
+        schedule: Option<Vec<u16>>,
+    ) -> Result<Response, ContractError> {
+        if let Some(schedule) = &schedule {
+            validate_payout_schedule(schedule)?;
+        }
+        let mut game = load_game(deps.storage, &game_id)?;
+        let previous = std::mem::replace(&mut game.payout_schedule, schedule);
+        GAMES.save(deps.storage, &game_id, &game)?;
+        Ok(Response::new().add_event(
+            Event::new("arcade_payout_schedule_updated")
+                .add_attribute("game_id", game_id)
+                .add_attribute("previous_schedule", to_json_string(&previous)?)
+                .add_attribute(
+                    "schedule",
+                    to_json_string(&game.payout_schedule)?,
+                ),
+        ))
+    }
+
+    pub fn distribute(
+        mut deps: DepsMut,
+        game_id: String,
+    ) -> Result<Response, ContractError> {
+        let season = CURRENT_SEASON.load(deps.storage, &game_id)?;
+        Ok(distribute_prizes(&mut deps, &game_id, season.id)?
+            .add_attribute("season_id", season.id.to_string()))
+    }
+
+    pub fn set_season_duration(
+        deps: DepsMut,
+        game_id: String,
+        duration: Option<SeasonDuration>,
+    ) -> Result<Response, ContractError> {
+        if let Some(SeasonDuration::Seconds(0) | SeasonDuration::Blocks(0)) =
+            duration
+        {
+            return Err(ContractError::ZeroSeasonDuration);
+        }
+        let mut game = load_game(deps.storage, &game_id)?;
+        let previous = std::mem::replace(&mut game.season_duration, duration);
+        GAMES.save(deps.storage, &game_id, &game)?;
+        Ok(Response::new().add_event(
+            Event::new("arcade_season_duration_updated")
+                .add_attribute("game_id", game_id)
+                .add_attribute("previous_duration", to_json_string(&previous)?)
+                .add_attribute(
+                    "duration",
+                    to_json_string(&game.season_duration)?,
+                ),
+        ))
+    }
+
+    pub fn end_season(
+        mut deps: DepsMut,
+        env: Env,
+        game_id: String,
+    ) -> Result<Response, ContractError> {
+        crate::utils::end_season(&mut deps, &env, &game_id)
+    }
+
+    pub fn set_trophy_config(
+        deps: DepsMut,
+        config: Option<TrophyConfig>,
+    ) -> Result<Response, ContractError> {
+        let previous = TROPHY_CONFIG.may_load(deps.storage)?;
+        match &config {
+            Some(config) => {
+                deps.api.addr_validate(config.contract.as_str())?;
+                TROPHY_CONFIG.save(deps.storage, config)?;
+            }
+            None => TROPHY_CONFIG.remove(deps.storage),
+        }
+        Ok(Response::new().add_event(
+            Event::new("arcade_trophy_config_updated")
+                .add_attribute("previous_config", to_json_string(&previous)?)
+                .add_attribute("config", to_json_string(&config)?),
+        ))
+    }
+}
+
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    use QueryMsg::*;
+
+    match msg {
+        AdminsList {} => to_json_binary(&query::admins_list(deps)?),
+        ScoreList {
+            game_id,
+            start_after,
+            limit,
+        } => to_json_binary(&query::scoreboard(
+            deps,
+            game_id,
+            start_after,
+            limit,
+        )?),
+        PlayerRank { game_id, address } => {
+            to_json_binary(&query::player_rank(deps, game_id, address)?)
+        }
+        GameCounter { game_id } => {
+            to_json_binary(&query::game_counter(deps, game_id)?)
+        }
+        Price { game_id } => to_json_binary(&query::get_price(deps, game_id)?),
+        Prices { game_id } => to_json_binary(&query::prices(deps, game_id)?),
+        PriceBounds {} => to_json_binary(&query::price_bounds(deps)?),
+        PaymentMode {} => to_json_binary(&query::payment_mode(deps)?),
+        Credits { game_id, address } => {
+            to_json_binary(&query::credits(deps, game_id, address)?)
+        }
+        BulkDiscounts { game_id } => {
+            to_json_binary(&query::bulk_discounts(deps, game_id)?)
+        }
+        NftPass { game_id } => to_json_binary(&query::nft_pass(deps, game_id)?),
+        NftPlaysLeft { game_id, token_id } => to_json_binary(
+            &query::nft_plays_left(deps, env, game_id, token_id)?,
+        ),
+        PrizePool { game_id } => {
+            to_json_binary(&query::prize_pool(deps, game_id)?)
+        }
+        TotalDistributed { game_id } => {
+            to_json_binary(&query::total_distributed(deps, game_id)?)
+        }
+        ServerKeys {} => to_json_binary(&query::server_keys(deps)?),
+        Session { id } => to_json_binary(&query::session(deps, env, id)?),
+        PlayerSessions {
+            address,
+            start_after,
+            limit,
+        } => to_json_binary(&query::player_sessions(
+            deps,
+            address,
+            start_after,
+            limit,
+        )?),
+        Game { game_id } => to_json_binary(&query::game(deps, game_id)?),
+        Games { start_after, limit } => {
+            to_json_binary(&query::games(deps, start_after, limit)?)
+        }
+        Surplus { denom } => to_json_binary(&query::surplus(deps, env, denom)?),
+        RevenueSplit {} => to_json_binary(&query::revenue_split(deps)?),
+        PayoutSchedule { game_id } => {
+            to_json_binary(&query::payout_schedule(deps, game_id)?)
+        }
+        PlayerStats { game_id, address } => {
+            to_json_binary(&query::player_stats(deps, game_id, address)?)
+        }
+        AllPlayers {
+            game_id,
+            start_after,
+            limit,
+        } => to_json_binary(&query::all_players(
+            deps,
+            game_id,
+            start_after,
+            limit,
+        )?),
+        CurrentSeason { game_id } => {
+            to_json_binary(&query::current_season(deps, game_id)?)
+        }
+        Seasons {
+            game_id,
+            start_after,
+            limit,
+        } => {
+            to_json_binary(&query::seasons(deps, game_id, start_after, limit)?)
+        }
+        Roles { address } => to_json_binary(&query::roles(deps, address)?),
+        Ownership {} => to_json_binary(&query::ownership(deps)?),
+        PauseState {} => to_json_binary(&query::pause_state(deps)?),
+        HeldPayouts { address } => {
+            to_json_binary(&query::held_payouts(deps, address)?)
+        }
+        TrophyConfig {} => to_json_binary(&query::trophy_config(deps)?),
+        RoleMembers {
+            role,
+            start_after,
+            limit,
+        } => to_json_binary(&query::role_members(
+            deps,
+            role,
+            start_after,
+            limit,
+        )?),
+    }
+}
+
+mod query {
+    use crate::msg::{
+        AllPlayersResp, BulkDiscountsResp, CreditsResp, CurrentSeasonResp,
+        GameResp, GamesResp, HeldPayoutsResp, NftPassResp, NftPlaysLeftResp,
+        OwnershipResp, PauseStateResp, PaymentModeResp, PayoutScheduleResp,
+        PlayerStatsResp, PriceBoundsResp, PricesResp, PrizePoolResp,
+        RevenueSplitResp, RoleMembersResp, RolesResp, SeasonsResp,
+        ServerKeyResp, ServerKeysResp, SessionResp, SessionsResp, SurplusResp,
+        TotalDistributionResp, TrophyConfigResp,
+    };
+    use crate::state::{
+        Asset, ACCEPTED_ASSETS, ASSET_DISTRIBUTED, BULK_DISCOUNTS, CREDITS,
+        CURRENT_SEASON, HELD_PAYOUTS, NFT_PASSES, NFT_PASS_USES, PAUSED,
+        PAYMENT_MODE, PENDING_OWNER, PLAYER_SESSIONS, PLAYER_STATS,
+        PRICE_BOUNDS, SEASONS, SESSIONS, TROPHIES_MINTED, TROPHY_CONFIG,
+    };
+    use crate::utils::{
+        admins, has_role, nft_pass_uses_on, prize_pools, ranked_scoreboard,
+        role_holders, session_expired, unaccounted_surplus,
+    };
+    use crate::{
+        msg::{
+            AdminsListResp, GameCounterResp, GamePriceResp, PlayerRankResp,
+            RankedUser, ScoreboardListResp,
+        },
+        state::SERVER_KEYS,
+    };
+    use cosmwasm_std::Order;
+    use cw_storage_plus::Bound;
+
+    use super::*;
+
+    const DEFAULT_LIMIT: u32 = 10;
+    const MAX_LIMIT: u32 = 30;
+
+    pub fn scoreboard(
+        deps: Deps,
+        game_id: String,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    ) -> StdResult<ScoreboardListResp> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let scores = ranked_scoreboard(deps.storage, &game_id)?
+            .into_iter()
+            .zip(1..)
+            .skip(start_after.unwrap_or_default() as usize)
+            .take(limit)
+            .map(|(entry, rank)| RankedUser { rank, entry })
+            .collect();
+        let resp = ScoreboardListResp { scores };
+        Ok(resp)
+    }
+
+    pub fn player_rank(
+        deps: Deps,
+        game_id: String,
+        address: String,
+    ) -> StdResult<PlayerRankResp> {
+        let address = deps.api.addr_validate(&address)?;
+        let rank = ranked_scoreboard(deps.storage, &game_id)?
+            .iter()
+            .position(|entry| entry.user.address == address)
+            .map(|index| index as u32 + 1);
+        Ok(PlayerRankResp { rank })
+    }
+
+    pub fn admins_list(deps: Deps) -> StdResult<AdminsListResp> {
+        let admins = admins(deps.storage)?;
+        let resp = AdminsListResp { admins };
+        Ok(resp)
+    }
+
+    pub fn roles(deps: Deps, address: String) -> StdResult<RolesResp> {
+        let address = deps.api.addr_validate(&address)?;
+        let roles = Role::ALL
+            .into_iter()
+            .filter(|role| has_role(deps.storage, &address, *role))
+            .collect();
+        Ok(RolesResp { roles })
+    }
+
+    pub fn role_members(
+        deps: Deps,
+        role: Role,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<RoleMembersResp> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start_after = start_after
+            .map(|addr| deps.api.addr_validate(&addr))
+            .transpose()?;
+        let members = ROLES
+            .prefix(role.as_str())
+            .keys(
+                deps.storage,
+                start_after.as_ref().map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .collect::<StdResult<_>>()?;
+        Ok(RoleMembersResp { members })
+    }
+
+    pub fn ownership(deps: Deps) -> StdResult<OwnershipResp> {
+        let owner = role_holders(deps.storage, Role::Owner)?
+            .pop()
+            .ok_or_else(|| StdError::not_found("owner"))?;
+        let pending_owner = PENDING_OWNER.may_load(deps.storage)?;
+        Ok(OwnershipResp {
+            owner,
+            pending_owner,
+        })
+    }
+
+    pub fn pause_state(deps: Deps) -> StdResult<PauseStateResp> {
+        let paused = PAUSED
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, pause)| pause))
+            .collect::<StdResult<_>>()?;
+        Ok(PauseStateResp { paused })
+    }
+
+    pub fn held_payouts(
+        deps: Deps,
+        address: String,
+    ) -> StdResult<HeldPayoutsResp> {
+        let address = deps.api.addr_validate(&address)?;
+        let payouts = HELD_PAYOUTS
+            .prefix(&address)
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| {
+                item.map(|(key, amount)| {
+                    Asset::new(AssetInfo::from_key(&key), amount)
+                })
+            })
+            .collect::<StdResult<_>>()?;
+        Ok(HeldPayoutsResp { payouts })
+    }
+
+    pub fn trophy_config(deps: Deps) -> StdResult<TrophyConfigResp> {
+        Ok(TrophyConfigResp {
+            config: TROPHY_CONFIG.may_load(deps.storage)?,
+            minted: TROPHIES_MINTED.may_load(deps.storage)?.unwrap_or_default(),
+        })
+    }
+
+    pub fn game_counter(
+        deps: Deps,
+        game_id: String,
+    ) -> StdResult<GameCounterResp> {
+        let counter = GAME_COUNTER.load(deps.storage, &game_id)?;
+        let resp = GameCounterResp {
+            game_counter: counter,
+        };
+        Ok(resp)
+    }
+
+    pub fn get_price(deps: Deps, game_id: String) -> StdResult<GamePriceResp> {
+        let price = GAMES.load(deps.storage, &game_id)?.price;
+        let resp = GamePriceResp { price };
+        Ok(resp)
+    }
+
+    pub fn payment_mode(deps: Deps) -> StdResult<PaymentModeResp> {
+        let mode = PAYMENT_MODE.may_load(deps.storage)?.unwrap_or_default();
+        Ok(PaymentModeResp { mode })
+    }
+
+    pub fn price_bounds(deps: Deps) -> StdResult<PriceBoundsResp> {
+        let bounds = PRICE_BOUNDS.may_load(deps.storage)?;
+        Ok(PriceBoundsResp { bounds })
+    }
+
+    pub fn credits(
+        deps: Deps,
+        game_id: String,
+        address: String,
+    ) -> StdResult<CreditsResp> {
+        let address = deps.api.addr_validate(&address)?;
+        let credits = CREDITS
+            .may_load(deps.storage, (&game_id, &address))?
+            .unwrap_or_default();
+        Ok(CreditsResp { credits })
+    }
+
+    pub fn bulk_discounts(
+        deps: Deps,
+        game_id: String,
+    ) -> StdResult<BulkDiscountsResp> {
+        let discounts = BULK_DISCOUNTS
+            .may_load(deps.storage, &game_id)?
+            .unwrap_or_default();
+        Ok(BulkDiscountsResp { discounts })
+    }
+
+    pub fn nft_pass(deps: Deps, game_id: String) -> StdResult<NftPassResp> {
+        Ok(NftPassResp {
+            pass: NFT_PASSES.may_load(deps.storage, &game_id)?,
+        })
+    }
+
+    pub fn nft_plays_left(
+        deps: Deps,
+        env: Env,
+        game_id: String,
+        token_id: String,
+    ) -> StdResult<NftPlaysLeftResp> {
+        let daily_plays = NFT_PASSES
+            .may_load(deps.storage, &game_id)?
+            .map_or(0, |pass| pass.daily_plays);
+        let uses = nft_pass_uses_on(
+            NFT_PASS_USES.may_load(deps.storage, (&game_id, &token_id))?,
+            env.block.time,
+        );
+        Ok(NftPlaysLeftResp {
+            plays_left: daily_plays.saturating_sub(uses.count),
+        })
+    }
+
+    pub fn prices(deps: Deps, game_id: String) -> StdResult<PricesResp> {
+        let game = GAMES.load(deps.storage, &game_id)?;
+        let mut prices = vec![Asset::new(game.asset, game.price)];
+        for item in ACCEPTED_ASSETS.prefix(&game_id).range(
+            deps.storage,
+            None,
+            None,
+            Order::Ascending,
+        ) {
+            let (key, price) = item?;
+            prices.push(Asset::new(AssetInfo::from_key(&key), price));
+        }
+        Ok(PricesResp { prices })
+    }
+
+    pub fn prize_pool(deps: Deps, game_id: String) -> StdResult<PrizePoolResp> {
+        // returns accounted amount of coins that will be distributed to the winner that hist the scoreboard
+        let game = GAMES.load(deps.storage, &game_id)?;
+        let prize_pool = PRIZE_POOL.load(deps.storage, &game_id)?;
+        let pools = prize_pools(deps.storage, &game_id, &game)?
+            .into_iter()
+            .map(|(asset, amount)| Asset::new(asset, amount))
+            .collect();
+        Ok(PrizePoolResp { prize_pool, pools })
+    }
+
+    pub fn total_distributed(
+        deps: Deps,
+        game_id: String,
+    ) -> StdResult<TotalDistributionResp> {
+        let game = GAMES.load(deps.storage, &game_id)?;
+        let total_distributed =
+            TOTAL_PRICE_DISTRIBUTED.load(deps.storage, &game_id)?;
+        let mut per_asset = vec![Asset::new(game.asset, total_distributed)];
+        for item in ASSET_DISTRIBUTED.prefix(&game_id).range(
+            deps.storage,
+            None,
+            None,
+            Order::Ascending,
+        ) {
+            let (key, total) = item?;
+            per_asset.push(Asset::new(AssetInfo::from_key(&key), total));
+        }
+        Ok(TotalDistributionResp {
+            total_distributed,
+            per_asset,
+        })
+    }
+
+    pub fn server_keys(deps: Deps) -> StdResult<ServerKeysResp> {
+        let keys = SERVER_KEYS
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| {
+                let (name, key) = item?;
+                Ok(ServerKeyResp {
+                    name,
+                    algorithm: key.algorithm,
+                    public_key: key.public_key,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+        Ok(ServerKeysResp { keys })
+    }
+
+    pub fn session(deps: Deps, env: Env, id: u64) -> StdResult<SessionResp> {
+        let session = SESSIONS.load(deps.storage, id)?;
+        let ttl = SESSION_TTL.load(deps.storage)?;
+        let expired = session_expired(&session, ttl, &env);
+        Ok(SessionResp { session, expired })
+    }
+
+    pub fn player_sessions(
+        deps: Deps,
+        address: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<SessionsResp> {
+        let player = deps.api.addr_validate(&address)?;
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let sessions = PLAYER_SESSIONS
+            .prefix(&player)
+            .keys(
+                deps.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|id| SESSIONS.load(deps.storage, id?))
+            .collect::<StdResult<_>>()?;
+        Ok(SessionsResp { sessions })
+    }
+
+    pub fn game(deps: Deps, game_id: String) -> StdResult<GameResp> {
+        let game = GAMES.load(deps.storage, &game_id)?;
+        Ok(GameResp { game_id, game })
+    }
+
+    pub fn games(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<GamesResp> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let games = GAMES
+            .range(
+                deps.storage,
+                start_after.as_deref().map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|item| {
+                let (game_id, game) = item?;
+                Ok(GameResp { game_id, game })
+            })
+            .collect::<StdResult<_>>()?;
+        Ok(GamesResp { games })
+    }
+
+    pub fn surplus(
+        deps: Deps,
+        env: Env,
+        denom: String,
+    ) -> StdResult<SurplusResp> {
+        let surplus = unaccounted_surplus(deps, &env, &denom)?;
+        Ok(SurplusResp { surplus })
+    }
+
+    pub fn revenue_split(deps: Deps) -> StdResult<RevenueSplitResp> {
+        let revenue_split = REVENUE_SPLIT.load(deps.storage)?;
+        Ok(RevenueSplitResp { revenue_split })
+    }
+
+    pub fn payout_schedule(
+        deps: Deps,
+        game_id: String,
+    ) -> StdResult<PayoutScheduleResp> {
+        let schedule = GAMES.load(deps.storage, &game_id)?.payout_schedule;
+        Ok(PayoutScheduleResp { schedule })
+    }
+
+    pub fn player_stats(
+        deps: Deps,
+        game_id: String,
+        address: String,
+    ) -> StdResult<PlayerStatsResp> {
+        let address = deps.api.addr_validate(&address)?;
+        let stats = PLAYER_STATS
+            .may_load(deps.storage, (&game_id, &address))?
+            .unwrap_or_default();
+        Ok(PlayerStatsResp { address, stats })
+    }
+
+    pub fn all_players(
+        deps: Deps,
+        game_id: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<AllPlayersResp> {
+        let start_after = start_after
+            .map(|addr| deps.api.addr_validate(&addr))
+            .transpose()?;
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let players = PLAYER_STATS
+            .prefix(&game_id)
+            .range(
+                deps.storage,
+                start_after.as_ref().map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|item| {
+                let (address, stats) = item?;
+                Ok(PlayerStatsResp { address, stats })
+            })
+            .collect::<StdResult<_>>()?;
+        Ok(AllPlayersResp { players })
+    }
+
+    pub fn current_season(
+        deps: Deps,
+        game_id: String,
+    ) -> StdResult<CurrentSeasonResp> {
+        let season = CURRENT_SEASON.load(deps.storage, &game_id)?;
+        Ok(CurrentSeasonResp { season })
+    }
+
+    pub fn seasons(
+        deps: Deps,
+        game_id: String,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    ) -> StdResult<SeasonsResp> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let seasons = SEASONS
+            .prefix(&game_id)
+            .range(
+                deps.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|item| Ok(item?.1))
+            .collect::<StdResult<_>>()?;
+        Ok(SeasonsResp { seasons })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::msg::{
+        AllPlayersResp, CreditsResp, CurrentSeasonResp, GamePriceResp,
+        GameResp, GamesResp, HeldPayoutsResp, NftPlaysLeftResp, OwnershipResp,
+        PauseStateResp, PaymentModeResp, PayoutScheduleResp, PlayerRankResp,
+        PlayerStatsResp, PricesResp, RevenueSplitResp, RoleMembersResp,
+        RolesResp, ScoreAttestation, SeasonsResp, SessionResp, SessionsResp,
+        SurplusResp, TotalDistributionResp, TrophyConfigResp,
+    };
+    use crate::state::{
+        Asset, BulkDiscount, Credits, EntryMode, Feature, KeyAlgorithm,
+        NftPass, PaymentMode, PlayerStats, PriceBounds, RevenueSplit,
+        ScoreOrder, SeasonDuration, Session, Share, TrophyConfig, TrophyKind,
+        TrophyMetadata,
+    };
+    use crate::utils::{attestation_digest, TOTAL_BPS};
+    use crate::{
+        msg::{
+            AdminsListResp, GameCounterResp, InstantiateMsg, PrizePoolResp,
+            QueryMsg, ReceiveMsg, ScoreboardListResp,
+        },
+        state::User,
+    };
+    use cosmwasm_std::{
+        coin, coins, Addr, Coin, Empty, Event, Uint128, Uint64,
+    };
+    use cw721::AllNftInfoResponse;
+    use cw_multi_test::{App, ContractWrapper, Executor};
+    use k256::ecdsa::signature::hazmat::PrehashSigner;
+
+    #[test]
+    fn play() {
+        let mut app = App::new(|router, _, storage| {
+            router
+                .bank
+                .init_balance(
+                    storage,
+                    &Addr::unchecked("user"),
+                    coins(5, "aconst"),
+                )
+                .unwrap()
+        });
+        let code =
+            ContractWrapper::new(execute, instantiate, query).with_reply(reply);
+        let code_id = app.store_code(Box::new(code));
+        let addr = app
+            .instantiate_contract(
+                code_id,
+                Addr::unchecked("wotori"),
+                &InstantiateMsg {
+                    arcade: "pacman".to_string(),
+                    admins: vec!["admin1".to_owned()],
+                    max_top_score: 10,
+                    price_peer_game: 1,
+                    session_ttl: 100,
+                    entry_mode: EntryMode::Multiple,
+                    score_order: ScoreOrder::HigherIsBetter,
+                    revenue_split: revenue_split(5000),
+                    denom: "aconst".to_string(),
+                },
+                &[],
+                "Pac-Man".to_string(),
+                None,
+            )
+            .unwrap();
+
+        let resp: GameCounterResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::GameCounter {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(resp, GameCounterResp { game_counter: 0 });
+
+        let _resp = app
+            .execute_contract(
+                Addr::unchecked("user"),
+                addr.clone(),
+                &ExecuteMsg::Play {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    token_id: None,
+                },
+                &coins(5, "aconst"),
+            )
+            .unwrap();
+
+        let resp: GameCounterResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::GameCounter {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(resp, GameCounterResp { game_counter: 1 })
+    }
+
+    #[test]
+    fn instantiation() {
+        let mut app = App::default();
+
+        let code =
+            ContractWrapper::new(execute, instantiate, query).with_reply(reply);
+        let code_id = app.store_code(Box::new(code));
+
+        let addr = app
+            .instantiate_contract(
+                code_id,
+                Addr::unchecked("owner"),
+                &InstantiateMsg {
+                    arcade: "pacman".to_string(),
+                    admins: vec![],
+                    max_top_score: 10,
+                    price_peer_game: 1,
+                    session_ttl: 100,
+                    entry_mode: EntryMode::Multiple,
+                    score_order: ScoreOrder::HigherIsBetter,
+                    revenue_split: revenue_split(5000),
+                    denom: "aconst".to_string(),
+                },
+                &[],
+                "Contract",
+                None,
+            )
+            .unwrap();
+
+        let resp: AdminsListResp = app
+            .wrap()
+            .query_wasm_smart(addr, &QueryMsg::AdminsList {})
+            .unwrap();
+        assert_eq!(resp, AdminsListResp { admins: vec![] });
+
+        let addr = app
+            .instantiate_contract(
+                code_id,
+                Addr::unchecked("owner"),
+                &InstantiateMsg {
+                    arcade: "Pac-Man".to_string(),
+                    admins: vec!["admin1".to_owned(), "admin2".to_owned()],
+                    max_top_score: 10,
+                    price_peer_game: 1,
+                    session_ttl: 100,
+                    entry_mode: EntryMode::Multiple,
+                    score_order: ScoreOrder::HigherIsBetter,
+                    revenue_split: revenue_split(5000),
+                    denom: "aconst".to_string(),
+                },
+                &[],
+                "Contract 2",
+                None,
+            )
+            .unwrap();
+
+        let resp: AdminsListResp = app
+            .wrap()
+            .query_wasm_smart(&addr, &QueryMsg::AdminsList {})
+            .unwrap();
+
+        assert_eq!(
+            resp,
+            AdminsListResp {
+                admins: vec![
+                    Addr::unchecked("admin1"),
+                    Addr::unchecked("admin2"),
+                ],
+            }
+        );
+    }
+
+    fn revenue_split(prize_pool_bps: u16) -> RevenueSplit {
+        RevenueSplit {
+            prize_pool_bps,
+            treasury: None,
+            developer: None,
+            operators_bps: TOTAL_BPS - prize_pool_bps,
+            burn_bps: 0,
+        }
+    }
+
+    fn test_user(name: &str, score: u64, addr: String) -> User {
+        User {
+            name: name.to_string(),
+            address: Addr::unchecked(addr),
+            score: Uint64::new(score),
+        }
+    }
+
+    fn ranked(resp: &ScoreboardListResp) -> Vec<(u32, User)> {
+        resp.scores
+            .iter()
+            .map(|score| (score.rank, score.entry.user.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn write_score() {
+        let mut app = App::new(|router, _, storage| {
+            router
+                .bank
+                .init_balance(
+                    storage,
+                    &Addr::unchecked("test"),
+                    coins(4, "aconst"),
+                )
+                .unwrap()
+        });
+
+        let code =
+            ContractWrapper::new(execute, instantiate, query).with_reply(reply);
+        let code_id = app.store_code(Box::new(code));
+        let max = 1;
+        let addr = app
+            .instantiate_contract(
+                code_id,
+                Addr::unchecked("wotori"),
+                &InstantiateMsg {
+                    admins: vec!["wotori".to_string()],
+                    arcade: "Pac-Man".to_string(),
+                    max_top_score: max,
+                    price_peer_game: 1,
+                    session_ttl: 100,
+                    entry_mode: EntryMode::Multiple,
+                    score_order: ScoreOrder::HigherIsBetter,
+                    revenue_split: revenue_split(5000),
+                    denom: "aconst".to_string(),
+                },
+                &[],
+                "cw-arcade",
+                None,
+            )
+            .unwrap();
+
+        for _ in 0..2 {
+            app.execute_contract(
+                Addr::unchecked("test"),
+                addr.clone(),
+                &ExecuteMsg::Play {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    token_id: None,
+                },
+                &coins(2, "aconst"),
+            )
+            .unwrap();
+        }
+
+        let user1 = test_user("user1", 299, "test".to_string());
+        let _resp = app
+            .execute_contract(
+                Addr::unchecked("wotori"),
+                addr.clone(),
+                &ExecuteMsg::AddTopUser {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    user: user1.clone(),
+                    session_id: 1,
+                },
+                &[],
+            )
+            .unwrap();
+
+        let user2 = test_user("user2", 300, "test".to_string());
+        let _resp = app
+            .execute_contract(
+                Addr::unchecked("wotori"),
+                addr.clone(),
+                &ExecuteMsg::AddTopUser {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    user: user2.clone(),
+                    session_id: 2,
+                },
+                &[],
+            )
+            .unwrap();
+
+        let resp: ScoreboardListResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::ScoreList {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+
+        assert_eq!(ranked(&resp), vec![(1, user2)]);
+
+        assert_eq!(resp.scores.len(), usize::from(max))
+    }
+
+    #[test]
+    fn unauthorized() {
+        let mut app = App::default();
+
+        let code =
+            ContractWrapper::new(execute, instantiate, query).with_reply(reply);
+        let code_id = app.store_code(Box::new(code));
+
+        let addr = app
+            .instantiate_contract(
+                code_id,
+                Addr::unchecked("owner"),
+                &InstantiateMsg {
+                    admins: vec![],
+                    arcade: "Pac-Man".to_string(),
+                    max_top_score: 10,
+                    price_peer_game: 1,
+                    session_ttl: 100,
+                    entry_mode: EntryMode::Multiple,
+                    score_order: ScoreOrder::HigherIsBetter,
+                    revenue_split: revenue_split(5000),
+                    denom: "aconst".to_string(),
+                },
+                &[],
+                "Contract",
+                None,
+            )
+            .unwrap();
+
+        let err = app
+            .execute_contract(
+                Addr::unchecked("user"),
+                addr,
+                &ExecuteMsg::AddAdmin {
+                    admins: vec!["user".to_owned()],
+                },
+                &[],
+            )
+            .unwrap_err();
+
+        assert_eq!(
+            ContractError::MissingRole {
+                sender: Addr::unchecked("user"),
+                role: Role::Owner,
+            },
+            err.downcast().unwrap()
+        );
+    }
+
+    #[test]
+    fn play_with_pay() {
+        let mut app = App::new(|router, _, storage| {
+            router
+                .bank
+                .init_balance(
+                    storage,
+                    &Addr::unchecked("user1"),
+                    coins(100000, "aconst"),
+                )
+                .unwrap();
+            router
+                .bank
+                .init_balance(
+                    storage,
+                    &Addr::unchecked("test2"),
+                    coins(123, "aconst"),
+                )
+                .unwrap()
+        });
+
+        let code =
+            ContractWrapper::new(execute, instantiate, query).with_reply(reply);
+        let code_id = app.store_code(Box::new(code));
+
+        let addr = app
+            .instantiate_contract(
+                code_id,
+                Addr::unchecked("owner"),
+                &InstantiateMsg {
+                    arcade: "pacman".to_string(),
+                    admins: vec!["admin1".to_owned(), "admin2".to_owned()],
+                    max_top_score: 1,
+                    price_peer_game: 123,
+                    session_ttl: 100,
+                    entry_mode: EntryMode::Multiple,
+                    score_order: ScoreOrder::HigherIsBetter,
+                    revenue_split: revenue_split(3334),
+                    denom: "aconst".to_string(),
+                },
+                &[],
+                "Pac-Man Arcade",
+                None,
+            )
+            .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("user1"),
+            addr.clone(),
+            &ExecuteMsg::Play {
+                game_id: DEFAULT_GAME_ID.to_string(),
+                token_id: None,
+            },
+            &coins(333, "aconst"),
+        )
+        .unwrap();
+
+        let resp: PrizePoolResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::PrizePool {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                },
+            )
+            .unwrap();
+        // the 210 sent above the price of 123 went back as change
+        assert_eq!(resp.prize_pool, 43);
+
+        let price: GamePriceResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::Price {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(price, GamePriceResp { price: 123 });
+
+        let price: GameCounterResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::GameCounter {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(price, GameCounterResp { game_counter: 1 });
+
+        assert_eq!(
+            app.wrap()
+                .query_balance("user1", "aconst")
+                .unwrap()
+                .amount
+                .u128(),
+            99877
+        );
+
+        assert_eq!(
+            app.wrap()
+                .query_balance(&addr, "aconst")
+                .unwrap()
+                .amount
+                .u128(),
+            43
+        );
+
+        assert_eq!(
+            app.wrap()
+                .query_balance("admin1", "aconst")
+                .unwrap()
+                .amount
+                .u128(),
+            40
+        );
+
+        assert_eq!(
+            app.wrap()
+                .query_balance("admin2", "aconst")
+                .unwrap()
+                .amount
+                .u128(),
+            40
+        );
+
+        let arcade_balance = app
+            .wrap()
+            .query_balance(addr.clone(), "aconst")
+            .unwrap()
+            .amount
+            .u128();
+
+        assert_eq!(arcade_balance, 43);
+
+        let user2_balance_1 = app
+            .wrap()
+            .query_balance("test2", "aconst")
+            .unwrap()
+            .amount
+            .u128();
+
+        assert_eq!(user2_balance_1, 123);
+
+        // every score submission needs its own paid session
+        app.execute_contract(
+            Addr::unchecked("test2"),
+            addr.clone(),
+            &ExecuteMsg::Play {
+                game_id: DEFAULT_GAME_ID.to_string(),
+                token_id: None,
+            },
+            &coins(123, "aconst"),
+        )
+        .unwrap();
+
+        let user1 = test_user("user1", 299, "user1".to_string());
+        let _resp = app
+            .execute_contract(
+                Addr::unchecked("admin1"),
+                addr.clone(),
+                &ExecuteMsg::AddTopUser {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    user: user1.clone(),
+                    session_id: 1,
+                },
+                &[],
+            )
+            .unwrap();
+
+        let user2 = test_user("user2", 300, "test2".to_string());
+        let _resp = app
+            .execute_contract(
+                Addr::unchecked("admin1"),
+                addr.clone(),
+                &ExecuteMsg::AddTopUser {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    user: user2.clone(),
+                    session_id: 2,
+                },
+                &[],
+            )
+            .unwrap();
+
+        let arcade_balance_2 = app
+            .wrap()
+            .query_balance(addr.clone(), "aconst")
+            .unwrap()
+            .amount
+            .u128();
+
+        let user2_balance_2 = app
+            .wrap()
+            .query_balance("test2", "aconst")
+            .unwrap()
+            .amount
+            .u128();
+
+        // arcade balance should be empty as the whole balance should be sent to the winner user
+        assert_eq!(arcade_balance_2, 0);
+        assert_eq!(user2_balance_2, 86)
+    }
+
+    fn secp256k1_server() -> (k256::ecdsa::SigningKey, Binary) {
+        let key =
+            k256::ecdsa::SigningKey::from_bytes(&[7u8; 32].into()).unwrap();
+        let public_key = key.verifying_key().to_encoded_point(true);
+        (key, Binary::from(public_key.as_bytes()))
+    }
+
+    fn ed25519_server() -> (ed25519_zebra::SigningKey, Binary) {
+        let key = ed25519_zebra::SigningKey::from([9u8; 32]);
+        let public_key = ed25519_zebra::VerificationKeyBytes::from(&key);
+        (key, Binary::from(public_key.as_ref()))
+    }
+
+    fn attestation(
+        contract: &Addr,
+        player: &str,
+        score: u64,
+        nonce: u64,
+    ) -> ScoreAttestation {
+        ScoreAttestation {
+            contract: contract.to_string(),
+            game_id: DEFAULT_GAME_ID.to_string(),
+            player: player.to_string(),
+            score: Uint64::new(score),
+            nonce,
+        }
+    }
+
+    fn funded_app(players: &[&str]) -> App {
+        App::new(|router, _, storage| {
+            for player in players {
+                router
+                    .bank
+                    .init_balance(
+                        storage,
+                        &Addr::unchecked(*player),
+                        coins(100, "aconst"),
+                    )
+                    .unwrap();
+            }
+        })
+    }
+
+    fn open_session(app: &mut App, addr: &Addr, player: &str) -> u64 {
+        open_game_session(app, addr, DEFAULT_GAME_ID, player)
+    }
+
+    fn open_game_session(
+        app: &mut App,
+        addr: &Addr,
+        game_id: &str,
+        player: &str,
+    ) -> u64 {
+        let resp = app
+            .execute_contract(
+                Addr::unchecked(player),
+                addr.clone(),
+                &ExecuteMsg::Play {
+                    game_id: game_id.to_string(),
+                    token_id: None,
+                },
+                &coins(1, "aconst"),
+            )
+            .unwrap();
+        let session_id = resp
+            .events
+            .iter()
+            .flat_map(|event| event.attributes.iter())
+            .find(|attr| attr.key == "session_id")
+            .unwrap();
+        session_id.value.parse().unwrap()
+    }
+
+    fn instantiate_signed_arcade(app: &mut App) -> Addr {
+        let code =
+            ContractWrapper::new(execute, instantiate, query).with_reply(reply);
+        let code_id = app.store_code(Box::new(code));
+        let addr = app
+            .instantiate_contract(
+                code_id,
+                Addr::unchecked("owner"),
+                &InstantiateMsg {
+                    arcade: "Pac-Man".to_string(),
+                    admins: vec!["admin1".to_owned()],
+                    max_top_score: 10,
+                    price_peer_game: 1,
+                    session_ttl: 100,
+                    entry_mode: EntryMode::Multiple,
+                    score_order: ScoreOrder::HigherIsBetter,
+                    revenue_split: revenue_split(5000),
+                    denom: "aconst".to_string(),
+                },
+                &[],
+                "Pac-Man Arcade",
+                None,
+            )
+            .unwrap();
+
+        let (_, secp256k1_pubkey) = secp256k1_server();
+        let (_, ed25519_pubkey) = ed25519_server();
+        for (name, algorithm, public_key) in [
+            ("server1", KeyAlgorithm::Secp256k1, secp256k1_pubkey),
+            ("server2", KeyAlgorithm::Ed25519, ed25519_pubkey),
+        ] {
+            app.execute_contract(
+                Addr::unchecked("admin1"),
+                addr.clone(),
+                &ExecuteMsg::RegisterServerKey {
+                    name: name.to_string(),
+                    algorithm,
+                    public_key,
+                },
+                &[],
+            )
+            .unwrap();
+        }
+        addr
+    }
+
+    #[test]
+    fn submit_signed_score() {
+        let mut app = funded_app(&["player1", "player2"]);
+        let addr = instantiate_signed_arcade(&mut app);
+        let session1 = open_session(&mut app, &addr, "player1");
+        let session2 = open_session(&mut app, &addr, "player2");
+
+        let (secp256k1_key, _) = secp256k1_server();
+        let digest =
+            attestation_digest(&attestation(&addr, "player1", 100, 1)).unwrap();
+        let signature: k256::ecdsa::Signature =
+            secp256k1_key.sign_prehash(&digest).unwrap();
+        app.execute_contract(
+            Addr::unchecked("player1"),
+            addr.clone(),
+            &ExecuteMsg::SubmitScore {
+                game_id: DEFAULT_GAME_ID.to_string(),
+                name: "Player 1".to_string(),
+                score: Uint64::new(100),
+                nonce: 1,
+                server: "server1".to_string(),
+                signature: Binary::from(&signature.to_bytes()[..]),
+                session_id: session1,
+            },
+            &[],
+        )
+        .unwrap();
+
+        let (ed25519_key, _) = ed25519_server();
+        let digest =
+            attestation_digest(&attestation(&addr, "player2", 200, 1)).unwrap();
+        let signature = ed25519_key.sign(&digest);
+        app.execute_contract(
+            Addr::unchecked("player2"),
+            addr.clone(),
+            &ExecuteMsg::SubmitScore {
+                game_id: DEFAULT_GAME_ID.to_string(),
+                name: "Player 2".to_string(),
+                score: Uint64::new(200),
+                nonce: 1,
+                server: "server2".to_string(),
+                signature: Binary::from(<[u8; 64]>::from(signature)),
+                session_id: session2,
+            },
+            &[],
+        )
+        .unwrap();
+
+        let resp: ScoreboardListResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::ScoreList {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            ranked(&resp),
+            vec![
+                (1, test_user("Player 2", 200, "player2".to_string())),
+                (2, test_user("Player 1", 100, "player1".to_string()))
+            ]
+        );
+    }
+
+    #[test]
+    fn submit_score_rejects_replay_and_forgery() {
+        let mut app = funded_app(&["player1"]);
+        let addr = instantiate_signed_arcade(&mut app);
+        let session_id = open_session(&mut app, &addr, "player1");
+
+        let (key, _) = secp256k1_server();
+        let digest =
+            attestation_digest(&attestation(&addr, "player1", 100, 1)).unwrap();
+        let signature: k256::ecdsa::Signature =
+            key.sign_prehash(&digest).unwrap();
+        let msg = ExecuteMsg::SubmitScore {
+            game_id: DEFAULT_GAME_ID.to_string(),
+            name: "Player 1".to_string(),
+            score: Uint64::new(100),
+            nonce: 1,
+            server: "server1".to_string(),
+            signature: Binary::from(&signature.to_bytes()[..]),
+            session_id,
+        };
+        app.execute_contract(
+            Addr::unchecked("player1"),
+            addr.clone(),
+            &msg,
+            &[],
+        )
+        .unwrap();
+
+        let err = app
+            .execute_contract(
+                Addr::unchecked("player1"),
+                addr.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::NonceUsed { nonce: 1 },
+            err.downcast().unwrap()
+        );
+
+        // the attestation was issued to player1 for score 100 only
+        let err = app
+            .execute_contract(
+                Addr::unchecked("player2"),
+                addr.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(ContractError::InvalidSignature, err.downcast().unwrap());
+
+        let err = app
+            .execute_contract(
+                Addr::unchecked("player1"),
+                addr.clone(),
+                &ExecuteMsg::SubmitScore {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    name: "Player 1".to_string(),
+                    score: Uint64::new(60000),
+                    nonce: 2,
+                    server: "server1".to_string(),
+                    signature: Binary::from(&signature.to_bytes()[..]),
+                    session_id,
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(ContractError::InvalidSignature, err.downcast().unwrap());
+
+        let err = app
+            .execute_contract(
+                Addr::unchecked("player1"),
+                addr,
+                &ExecuteMsg::SubmitScore {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    name: "Player 1".to_string(),
+                    score: Uint64::new(100),
+                    nonce: 3,
+                    server: "server3".to_string(),
+                    signature: Binary::from(&signature.to_bytes()[..]),
+                    session_id,
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::UnknownServerKey {
+                name: "server3".to_string()
+            },
+            err.downcast().unwrap()
+        );
+    }
+
+    #[test]
+    fn register_server_key_unauthorized() {
+        let mut app = App::default();
+        let addr = instantiate_signed_arcade(&mut app);
+        let (_, public_key) = ed25519_server();
+
+        let err = app
+            .execute_contract(
+                Addr::unchecked("player1"),
+                addr,
+                &ExecuteMsg::RegisterServerKey {
+                    name: "rogue".to_string(),
+                    algorithm: KeyAlgorithm::Ed25519,
+                    public_key,
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::MissingRole {
+                sender: Addr::unchecked("player1"),
+                role: Role::ConfigManager,
+            },
+            err.downcast().unwrap()
+        );
+    }
+
+    #[test]
+    fn score_requires_open_session() {
+        let mut app = funded_app(&["player1", "player2"]);
+        let addr = instantiate_signed_arcade(&mut app);
+        let session_id = open_session(&mut app, &addr, "player1");
+
+        let resp: SessionResp = app
+            .wrap()
+            .query_wasm_smart(&addr, &QueryMsg::Session { id: session_id })
+            .unwrap();
+        assert_eq!(
+            resp,
+            SessionResp {
+                session: Session {
+                    id: session_id,
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    player: Addr::unchecked("player1"),
+                    height: app.block_info().height,
+                    price_paid: 1,
+                    closed: false,
+                },
+                expired: false,
+            }
+        );
+
+        let add_score = |user: &str, session_id: u64| ExecuteMsg::AddTopUser {
+            game_id: DEFAULT_GAME_ID.to_string(),
+            user: test_user(user, 100, user.to_string()),
+            session_id,
+        };
+        let err = app
+            .execute_contract(
+                Addr::unchecked("admin1"),
+                addr.clone(),
+                &add_score("player2", session_id),
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::SessionPlayerMismatch {
+                id: session_id,
+                player: Addr::unchecked("player2"),
+            },
+            err.downcast().unwrap()
+        );
+
+        let err = app
+            .execute_contract(
+                Addr::unchecked("admin1"),
+                addr.clone(),
+                &add_score("player1", 42),
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::SessionNotFound { id: 42 },
+            err.downcast().unwrap()
+        );
+
+        app.execute_contract(
+            Addr::unchecked("admin1"),
+            addr.clone(),
+            &add_score("player1", session_id),
+            &[],
+        )
+        .unwrap();
+
+        let err = app
+            .execute_contract(
+                Addr::unchecked("admin1"),
+                addr.clone(),
+                &add_score("player1", session_id),
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::SessionClosed { id: session_id },
+            err.downcast().unwrap()
+        );
+
+        let resp: SessionsResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::PlayerSessions {
+                    address: "player1".to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(resp.sessions.len(), 1);
+        assert!(resp.sessions[0].closed);
+    }
+
+    #[test]
+    fn session_expires() {
+        let mut app = funded_app(&["player1"]);
+        let addr = instantiate_signed_arcade(&mut app);
+        let session_id = open_session(&mut app, &addr, "player1");
+
+        app.update_block(|block| block.height += 101);
+
+        let resp: SessionResp = app
+            .wrap()
+            .query_wasm_smart(&addr, &QueryMsg::Session { id: session_id })
+            .unwrap();
+        assert!(resp.expired);
+
+        let err = app
+            .execute_contract(
+                Addr::unchecked("admin1"),
+                addr.clone(),
+                &ExecuteMsg::AddTopUser {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    user: test_user("player1", 100, "player1".to_string()),
+                    session_id,
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::SessionExpired { id: session_id },
+            err.downcast().unwrap()
+        );
+
+        app.execute_contract(
+            Addr::unchecked("admin1"),
+            addr.clone(),
+            &ExecuteMsg::UpdateSessionTtl { blocks: 1000 },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            Addr::unchecked("admin1"),
+            addr.clone(),
+            &ExecuteMsg::AddTopUser {
+                game_id: DEFAULT_GAME_ID.to_string(),
+                user: test_user("player1", 100, "player1".to_string()),
+                session_id,
+            },
+            &[],
+        )
+        .unwrap();
+
+        // the largest ttl keeps sessions open instead of overflowing
+        app.execute_contract(
+            Addr::unchecked("admin1"),
+            addr.clone(),
+            &ExecuteMsg::UpdateSessionTtl { blocks: u64::MAX },
+            &[],
+        )
+        .unwrap();
+        let session_id = open_session(&mut app, &addr, "player1");
+        app.update_block(|block| block.height += 1000);
+        app.execute_contract(
+            Addr::unchecked("admin1"),
+            addr,
+            &ExecuteMsg::AddTopUser {
+                game_id: DEFAULT_GAME_ID.to_string(),
+                user: test_user("player1", 200, "player1".to_string()),
+                session_id,
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn games_are_isolated() {
+        let mut app = App::new(|router, _, storage| {
+            router
+                .bank
+                .init_balance(
                     storage,
-                    &Addr::unchecked("user"),
-                    coins(5, "aconst"),
+                    &Addr::unchecked("player1"),
+                    vec![coin(100, "aconst"), coin(100, "utetris")],
+                )
+                .unwrap()
+        });
+        let addr = instantiate_signed_arcade(&mut app);
+
+        let err = app
+            .execute_contract(
+                Addr::unchecked("player1"),
+                addr.clone(),
+                &ExecuteMsg::AddGame {
+                    game_id: "tetris".to_string(),
+                    name: "Tetris".to_string(),
+                    asset: AssetInfo::Native("utetris".to_string()),
+                    price: 10,
+                    max_top_score: 3,
+                    entry_mode: EntryMode::Multiple,
+                    score_order: ScoreOrder::HigherIsBetter,
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::MissingRole {
+                sender: Addr::unchecked("player1"),
+                role: Role::ConfigManager,
+            },
+            err.downcast().unwrap()
+        );
+
+        let add_tetris = ExecuteMsg::AddGame {
+            game_id: "tetris".to_string(),
+            name: "Tetris".to_string(),
+            asset: AssetInfo::Native("utetris".to_string()),
+            price: 10,
+            max_top_score: 3,
+            entry_mode: EntryMode::Multiple,
+            score_order: ScoreOrder::HigherIsBetter,
+        };
+        app.execute_contract(
+            Addr::unchecked("admin1"),
+            addr.clone(),
+            &add_tetris,
+            &[],
+        )
+        .unwrap();
+        let err = app
+            .execute_contract(
+                Addr::unchecked("admin1"),
+                addr.clone(),
+                &add_tetris,
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::GameExists {
+                game_id: "tetris".to_string()
+            },
+            err.downcast().unwrap()
+        );
+
+        let pacman_session = open_session(&mut app, &addr, "player1");
+        app.execute_contract(
+            Addr::unchecked("player1"),
+            addr.clone(),
+            &ExecuteMsg::Play {
+                game_id: "tetris".to_string(),
+                token_id: None,
+            },
+            &coins(10, "utetris"),
+        )
+        .unwrap();
+        let tetris_session = pacman_session + 1;
+
+        let prize_pool = |app: &App, game_id: &str| -> u128 {
+            let resp: PrizePoolResp = app
+                .wrap()
+                .query_wasm_smart(
+                    &addr,
+                    &QueryMsg::PrizePool {
+                        game_id: game_id.to_string(),
+                    },
                 )
+                .unwrap();
+            resp.prize_pool
+        };
+        assert_eq!(prize_pool(&app, DEFAULT_GAME_ID), 1);
+        assert_eq!(prize_pool(&app, "tetris"), 5);
+
+        // a pac-man session can't back a tetris score
+        let err = app
+            .execute_contract(
+                Addr::unchecked("admin1"),
+                addr.clone(),
+                &ExecuteMsg::AddTopUser {
+                    game_id: "tetris".to_string(),
+                    user: test_user("player1", 100, "player1".to_string()),
+                    session_id: pacman_session,
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::SessionGameMismatch {
+                id: pacman_session,
+                game_id: "tetris".to_string(),
+            },
+            err.downcast().unwrap()
+        );
+
+        app.execute_contract(
+            Addr::unchecked("admin1"),
+            addr.clone(),
+            &ExecuteMsg::AddTopUser {
+                game_id: "tetris".to_string(),
+                user: test_user("player1", 100, "player1".to_string()),
+                session_id: tetris_session,
+            },
+            &[],
+        )
+        .unwrap();
+
+        let resp: ScoreboardListResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::ScoreList {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert!(resp.scores.is_empty());
+        let resp: ScoreboardListResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::ScoreList {
+                    game_id: "tetris".to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(resp.scores.len(), 1);
+
+        let resp: GamesResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::Games {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        let ids: Vec<_> =
+            resp.games.iter().map(|g| g.game_id.as_str()).collect();
+        assert_eq!(ids, vec![DEFAULT_GAME_ID, "tetris"]);
+    }
+
+    #[test]
+    fn paused_and_retired_games() {
+        let mut app = funded_app(&["player1"]);
+        let addr = instantiate_signed_arcade(&mut app);
+        let session_id = open_session(&mut app, &addr, "player1");
+
+        app.execute_contract(
+            Addr::unchecked("admin1"),
+            addr.clone(),
+            &ExecuteMsg::PauseGame {
+                game_id: DEFAULT_GAME_ID.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+        let err = app
+            .execute_contract(
+                Addr::unchecked("player1"),
+                addr.clone(),
+                &ExecuteMsg::Play {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    token_id: None,
+                },
+                &coins(2, "aconst"),
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::GamePaused {
+                game_id: DEFAULT_GAME_ID.to_string()
+            },
+            err.downcast().unwrap()
+        );
+
+        app.execute_contract(
+            Addr::unchecked("admin1"),
+            addr.clone(),
+            &ExecuteMsg::ResumeGame {
+                game_id: DEFAULT_GAME_ID.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+        open_session(&mut app, &addr, "player1");
+
+        app.execute_contract(
+            Addr::unchecked("admin1"),
+            addr.clone(),
+            &ExecuteMsg::RetireGame {
+                game_id: DEFAULT_GAME_ID.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+        let err = app
+            .execute_contract(
+                Addr::unchecked("admin1"),
+                addr.clone(),
+                &ExecuteMsg::AddTopUser {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    user: test_user("player1", 100, "player1".to_string()),
+                    session_id,
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::GameRetired {
+                game_id: DEFAULT_GAME_ID.to_string()
+            },
+            err.downcast().unwrap()
+        );
+
+        let err = app
+            .execute_contract(
+                Addr::unchecked("admin1"),
+                addr.clone(),
+                &ExecuteMsg::ResumeGame {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::GameRetired {
+                game_id: DEFAULT_GAME_ID.to_string()
+            },
+            err.downcast().unwrap()
+        );
+
+        let resp: GameResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::Game {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(resp.game.status, GameStatus::Retired);
+    }
+
+    #[test]
+    fn prize_pool_is_accounted() {
+        let mut app = funded_app(&["player1", "sponsor", "stranger"]);
+        let addr = instantiate_signed_arcade(&mut app);
+        let session_id = open_session(&mut app, &addr, "player1");
+
+        // a plain bank transfer is not part of any prize pool
+        app.send_tokens(
+            Addr::unchecked("stranger"),
+            addr.clone(),
+            &coins(30, "aconst"),
+        )
+        .unwrap();
+        app.execute_contract(
+            Addr::unchecked("sponsor"),
+            addr.clone(),
+            &ExecuteMsg::FundPrizePool {
+                game_id: DEFAULT_GAME_ID.to_string(),
+            },
+            &coins(50, "aconst"),
+        )
+        .unwrap();
+
+        let resp: PrizePoolResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::PrizePool {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(resp.prize_pool, 51);
+        let resp: SurplusResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::Surplus {
+                    denom: "aconst".to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(resp, SurplusResp { surplus: 30 });
+
+        let sweep = ExecuteMsg::SweepSurplus {
+            denom: "aconst".to_string(),
+            recipient: "treasury".to_string(),
+        };
+        let err = app
+            .execute_contract(
+                Addr::unchecked("stranger"),
+                addr.clone(),
+                &sweep,
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::MissingRole {
+                sender: Addr::unchecked("stranger"),
+                role: Role::Treasurer,
+            },
+            err.downcast().unwrap()
+        );
+        app.execute_contract(
+            Addr::unchecked("admin1"),
+            addr.clone(),
+            &sweep,
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            app.wrap()
+                .query_balance("treasury", "aconst")
+                .unwrap()
+                .amount
+                .u128(),
+            30
+        );
+        let err = app
+            .execute_contract(
+                Addr::unchecked("admin1"),
+                addr.clone(),
+                &sweep,
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(ContractError::NoFunds, err.downcast().unwrap());
+
+        // fill the board, then beat the leader
+        let mut session = session_id;
+        for score in 1..=10 {
+            app.execute_contract(
+                Addr::unchecked("admin1"),
+                addr.clone(),
+                &ExecuteMsg::AddTopUser {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    user: test_user("player1", score, "player1".to_string()),
+                    session_id: session,
+                },
+                &[],
+            )
+            .unwrap();
+            if score < 10 {
+                session = open_session(&mut app, &addr, "player1");
+            }
+        }
+        let winner_session = open_session(&mut app, &addr, "sponsor");
+        app.execute_contract(
+            Addr::unchecked("admin1"),
+            addr.clone(),
+            &ExecuteMsg::AddTopUser {
+                game_id: DEFAULT_GAME_ID.to_string(),
+                user: test_user("sponsor", 500, "sponsor".to_string()),
+                session_id: winner_session,
+            },
+            &[],
+        )
+        .unwrap();
+
+        // 50 funded + 11 plays of 1 coin each, the whole pool is paid out
+        assert_eq!(
+            app.wrap()
+                .query_balance(&addr, "aconst")
+                .unwrap()
+                .amount
+                .u128(),
+            0
+        );
+        let resp: TotalDistributionResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::TotalDistributed {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(resp.total_distributed, 61);
+    }
+
+    #[test]
+    fn play_with_revenue_split() {
+        let mut app = funded_app(&["player1"]);
+        let addr = instantiate_signed_arcade(&mut app);
+
+        let mut split = RevenueSplit {
+            prize_pool_bps: 5000,
+            treasury: Some(Share {
+                address: Addr::unchecked("treasury"),
+                bps: 2000,
+            }),
+            developer: Some(Share {
+                address: Addr::unchecked("developer"),
+                bps: 1500,
+            }),
+            operators_bps: 1000,
+            burn_bps: 1000,
+        };
+        let err = app
+            .execute_contract(
+                Addr::unchecked("admin1"),
+                addr.clone(),
+                &ExecuteMsg::UpdateRevenueSplit {
+                    revenue_split: split.clone(),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::InvalidRevenueSplit { total: 10500 },
+            err.downcast().unwrap()
+        );
+
+        split.burn_bps = 500;
+        app.execute_contract(
+            Addr::unchecked("admin1"),
+            addr.clone(),
+            &ExecuteMsg::UpdateRevenueSplit {
+                revenue_split: split.clone(),
+            },
+            &[],
+        )
+        .unwrap();
+        let resp: RevenueSplitResp = app
+            .wrap()
+            .query_wasm_smart(&addr, &QueryMsg::RevenueSplit {})
+            .unwrap();
+        assert_eq!(resp.revenue_split, split);
+
+        app.execute_contract(
+            Addr::unchecked("admin1"),
+            addr.clone(),
+            &ExecuteMsg::UpdatePrice {
+                game_id: DEFAULT_GAME_ID.to_string(),
+                price: 99,
+            },
+            &[],
+        )
+        .unwrap();
+        let resp = app
+            .execute_contract(
+                Addr::unchecked("player1"),
+                addr.clone(),
+                &ExecuteMsg::Play {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    token_id: None,
+                },
+                &coins(99, "aconst"),
+            )
+            .unwrap();
+        let wasm = resp.events.iter().find(|e| e.ty == "wasm").unwrap();
+        let attr = |key: &str| {
+            wasm.attributes
+                .iter()
+                .find(|attr| attr.key == key)
+                .unwrap()
+                .value
+                .clone()
+        };
+        assert_eq!(attr("prize_pool_share"), "49");
+        assert_eq!(attr("treasury_share"), "19");
+        assert_eq!(attr("developer_share"), "14");
+        assert_eq!(attr("operator_share"), "9");
+        assert_eq!(attr("burn_share"), "4");
+        assert_eq!(attr("dust"), "4");
+
+        let balance = |app: &App, address: &str| {
+            app.wrap()
+                .query_balance(address, "aconst")
+                .unwrap()
+                .amount
+                .u128()
+        };
+        assert_eq!(balance(&app, "treasury"), 19);
+        assert_eq!(balance(&app, "developer"), 14);
+        assert_eq!(balance(&app, "admin1"), 9);
+        assert_eq!(balance(&app, addr.as_str()), 53);
+        let resp: PrizePoolResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::PrizePool {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(resp.prize_pool, 53);
+    }
+
+    #[test]
+    fn tiered_payout() {
+        let mut app = funded_app(&["player1", "player2", "player3", "sponsor"]);
+        let addr = instantiate_signed_arcade(&mut app);
+
+        let err = app
+            .execute_contract(
+                Addr::unchecked("admin1"),
+                addr.clone(),
+                &ExecuteMsg::Distribute {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::NoPayoutSchedule {
+                game_id: DEFAULT_GAME_ID.to_string()
+            },
+            err.downcast().unwrap()
+        );
+
+        let err = app
+            .execute_contract(
+                Addr::unchecked("admin1"),
+                addr.clone(),
+                &ExecuteMsg::SetPayoutSchedule {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    schedule: Some(vec![6000, 3000, 2000]),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::InvalidPayoutSchedule { total: 11000 },
+            err.downcast().unwrap()
+        );
+
+        app.execute_contract(
+            Addr::unchecked("admin1"),
+            addr.clone(),
+            &ExecuteMsg::SetPayoutSchedule {
+                game_id: DEFAULT_GAME_ID.to_string(),
+                schedule: Some(vec![5000, 3000, 2000]),
+            },
+            &[],
+        )
+        .unwrap();
+        let resp: PayoutScheduleResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::PayoutSchedule {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(resp.schedule, Some(vec![5000, 3000, 2000]));
+
+        for (player, score) in
+            [("player1", 300), ("player2", 100), ("player3", 200)]
+        {
+            let session_id = open_session(&mut app, &addr, player);
+            app.execute_contract(
+                Addr::unchecked("admin1"),
+                addr.clone(),
+                &ExecuteMsg::AddTopUser {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    user: test_user(player, score, player.to_string()),
+                    session_id,
+                },
+                &[],
+            )
+            .unwrap();
+        }
+        app.execute_contract(
+            Addr::unchecked("sponsor"),
+            addr.clone(),
+            &ExecuteMsg::FundPrizePool {
+                game_id: DEFAULT_GAME_ID.to_string(),
+            },
+            &coins(97, "aconst"),
+        )
+        .unwrap();
+
+        let err = app
+            .execute_contract(
+                Addr::unchecked("player1"),
+                addr.clone(),
+                &ExecuteMsg::Distribute {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::MissingRole {
+                sender: Addr::unchecked("player1"),
+                role: Role::Treasurer,
+            },
+            err.downcast().unwrap()
+        );
+        app.execute_contract(
+            Addr::unchecked("admin1"),
+            addr.clone(),
+            &ExecuteMsg::Distribute {
+                game_id: DEFAULT_GAME_ID.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        let balance = |app: &App, address: &str| {
+            app.wrap()
+                .query_balance(address, "aconst")
                 .unwrap()
-        });
-        let code = ContractWrapper::new(execute, instantiate, query);
-        let code_id = app.store_code(Box::new(code));
-        let addr = app
-            .instantiate_contract(
-                code_id,
-                Addr::unchecked("wotori"),
-                &InstantiateMsg {
-                    arcade: "pacman".to_string(),
-                    admins: vec!["admin1".to_owned()],
+                .amount
+                .u128()
+        };
+        // every player started with 100 and paid 1 for the game
+        assert_eq!(balance(&app, "player1"), 99 + 50);
+        assert_eq!(balance(&app, "player3"), 99 + 30);
+        assert_eq!(balance(&app, "player2"), 99 + 20);
+
+        let resp: TotalDistributionResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::TotalDistributed {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(resp.total_distributed, 100);
+        let resp: PrizePoolResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::PrizePool {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(resp.prize_pool, 0);
+    }
+
+    #[test]
+    fn seasons() {
+        let mut app = funded_app(&["player1", "player2"]);
+        let addr = instantiate_signed_arcade(&mut app);
+        let attribute = |resp: &cw_multi_test::AppResponse, key: &str| {
+            resp.events
+                .iter()
+                .flat_map(|event| event.attributes.iter())
+                .find(|attr| attr.key == key)
+                .map(|attr| attr.value.clone())
+        };
+
+        let err = app
+            .execute_contract(
+                Addr::unchecked("player1"),
+                addr.clone(),
+                &ExecuteMsg::EndSeason {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::MissingRole {
+                sender: Addr::unchecked("player1"),
+                role: Role::ConfigManager,
+            },
+            err.downcast().unwrap()
+        );
+        app.execute_contract(
+            Addr::unchecked("admin1"),
+            addr.clone(),
+            &ExecuteMsg::SetSeasonDuration {
+                game_id: DEFAULT_GAME_ID.to_string(),
+                duration: Some(SeasonDuration::Blocks(10)),
+            },
+            &[],
+        )
+        .unwrap();
+
+        let session_id = open_session(&mut app, &addr, "player1");
+        let resp = app
+            .execute_contract(
+                Addr::unchecked("admin1"),
+                addr.clone(),
+                &ExecuteMsg::AddTopUser {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    user: test_user("player1", 100, "player1".to_string()),
+                    session_id,
+                },
+                &[],
+            )
+            .unwrap();
+        assert_eq!(attribute(&resp, "season_id"), Some("1".to_string()));
+
+        // the first play after the season duration closes the season
+        app.update_block(|block| block.height += 10);
+        let resp = app
+            .execute_contract(
+                Addr::unchecked("player2"),
+                addr.clone(),
+                &ExecuteMsg::Play {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    token_id: None,
+                },
+                &coins(2, "aconst"),
+            )
+            .unwrap();
+        assert_eq!(attribute(&resp, "ended_season_id"), Some("1".to_string()));
+        assert_eq!(attribute(&resp, "season_id"), Some("2".to_string()));
+
+        let resp: ScoreboardListResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::ScoreList {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(resp.scores, vec![]);
+        let resp: CurrentSeasonResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::CurrentSeason {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(resp.season.id, 2);
+        assert_eq!(resp.season.games_at_start, 1);
+
+        app.execute_contract(
+            Addr::unchecked("admin1"),
+            addr.clone(),
+            &ExecuteMsg::EndSeason {
+                game_id: DEFAULT_GAME_ID.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        let resp: SeasonsResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::Seasons {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(resp.seasons.len(), 2);
+        let first = &resp.seasons[0];
+        assert_eq!(first.id, 1);
+        assert_eq!(
+            first.winner.as_ref().map(|entry| &entry.user),
+            Some(&test_user("player1", 100, "player1".to_string()))
+        );
+        assert_eq!(first.games_played, 1);
+        assert_eq!(first.distributed, 0);
+        let second = &resp.seasons[1];
+        assert_eq!(second.id, 2);
+        assert_eq!(second.winner, None);
+        assert_eq!(second.games_played, 1);
+        assert_eq!(second.start_height, first.end_height);
+
+        let resp: SeasonsResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::Seasons {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    start_after: Some(1),
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(resp.seasons, vec![second.clone()]);
+
+        // durations are positive and the longest ones never end a season
+        let set_duration = |app: &mut App, duration| {
+            app.execute_contract(
+                Addr::unchecked("admin1"),
+                addr.clone(),
+                &ExecuteMsg::SetSeasonDuration {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    duration: Some(duration),
+                },
+                &[],
+            )
+            .map_err(|err| err.downcast::<ContractError>().unwrap())
+        };
+        assert_eq!(
+            set_duration(&mut app, SeasonDuration::Seconds(0)).unwrap_err(),
+            ContractError::ZeroSeasonDuration
+        );
+        for duration in [
+            SeasonDuration::Seconds(u64::MAX),
+            SeasonDuration::Blocks(u64::MAX),
+        ] {
+            set_duration(&mut app, duration).unwrap();
+            app.update_block(|block| {
+                block.height += 1000;
+                block.time = block.time.plus_seconds(1000);
+            });
+            let resp = app
+                .execute_contract(
+                    Addr::unchecked("player2"),
+                    addr.clone(),
+                    &ExecuteMsg::Play {
+                        game_id: DEFAULT_GAME_ID.to_string(),
+                        token_id: None,
+                    },
+                    &coins(2, "aconst"),
+                )
+                .unwrap();
+            assert_eq!(attribute(&resp, "ended_season_id"), None);
+        }
+    }
+
+    #[test]
+    fn player_stats() {
+        let mut app = funded_app(&["player1", "player2"]);
+        let addr = instantiate_signed_arcade(&mut app);
+        app.execute_contract(
+            Addr::unchecked("admin1"),
+            addr.clone(),
+            &ExecuteMsg::SetPayoutSchedule {
+                game_id: DEFAULT_GAME_ID.to_string(),
+                schedule: Some(vec![10000]),
+            },
+            &[],
+        )
+        .unwrap();
+
+        for (player, score) in
+            [("player1", 100), ("player1", 80), ("player2", 50)]
+        {
+            let session_id = open_session(&mut app, &addr, player);
+            app.execute_contract(
+                Addr::unchecked("admin1"),
+                addr.clone(),
+                &ExecuteMsg::AddTopUser {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    user: test_user(player, score, player.to_string()),
+                    session_id,
+                },
+                &[],
+            )
+            .unwrap();
+        }
+        app.execute_contract(
+            Addr::unchecked("admin1"),
+            addr.clone(),
+            &ExecuteMsg::Distribute {
+                game_id: DEFAULT_GAME_ID.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        let resp: PlayerStatsResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::PlayerStats {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    address: "player1".to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            resp.stats,
+            PlayerStats {
+                personal_best: Some(Uint64::new(100)),
+                games_played: 2,
+                total_spent: 2,
+                // each 1 coin play is rounding dust of the split and goes to
+                // the prize pool
+                total_winnings: 3,
+                last_played: Some(app.block_info().time),
+            }
+        );
+
+        let resp: AllPlayersResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::AllPlayers {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    start_after: Some("player1".to_string()),
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(resp.players.len(), 1);
+        assert_eq!(resp.players[0].address, Addr::unchecked("player2"));
+        assert_eq!(resp.players[0].stats.personal_best, Some(Uint64::new(50)));
+        assert_eq!(resp.players[0].stats.total_winnings, 0);
+    }
+
+    #[test]
+    fn ranked_scoreboard() {
+        let mut app = funded_app(&["player1", "player2", "player3"]);
+        let addr = instantiate_signed_arcade(&mut app);
+        for (player, score) in
+            [("player1", 200), ("player2", 300_000), ("player3", 100)]
+        {
+            let session_id = open_session(&mut app, &addr, player);
+            app.execute_contract(
+                Addr::unchecked("admin1"),
+                addr.clone(),
+                &ExecuteMsg::AddTopUser {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    user: test_user(player, score, player.to_string()),
+                    session_id,
+                },
+                &[],
+            )
+            .unwrap();
+        }
+
+        let resp: ScoreboardListResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::ScoreList {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    start_after: None,
+                    limit: Some(2),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            ranked(&resp),
+            vec![
+                (1, test_user("player2", 300_000, "player2".to_string())),
+                (2, test_user("player1", 200, "player1".to_string()))
+            ]
+        );
+        let resp: ScoreboardListResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::ScoreList {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    start_after: Some(2),
+                    limit: Some(2),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            ranked(&resp),
+            vec![(3, test_user("player3", 100, "player3".to_string()))]
+        );
+
+        let rank = |app: &App, address: &str| {
+            let resp: PlayerRankResp = app
+                .wrap()
+                .query_wasm_smart(
+                    &addr,
+                    &QueryMsg::PlayerRank {
+                        game_id: DEFAULT_GAME_ID.to_string(),
+                        address: address.to_string(),
+                    },
+                )
+                .unwrap();
+            resp.rank
+        };
+        assert_eq!(rank(&app, "player1"), Some(2));
+        assert_eq!(rank(&app, "player3"), Some(3));
+        assert_eq!(rank(&app, "admin1"), None);
+    }
+
+    #[test]
+    fn one_entry_per_address() {
+        let mut app = funded_app(&["player1", "player2"]);
+        let addr = instantiate_signed_arcade(&mut app);
+        app.execute_contract(
+            Addr::unchecked("admin1"),
+            addr.clone(),
+            &ExecuteMsg::AddGame {
+                game_id: "tetris".to_string(),
+                name: "Tetris".to_string(),
+                asset: AssetInfo::Native("aconst".to_string()),
+                price: 1,
+                max_top_score: 2,
+                entry_mode: EntryMode::OnePerAddress,
+                score_order: ScoreOrder::HigherIsBetter,
+            },
+            &[],
+        )
+        .unwrap();
+
+        for (player, score) in [
+            ("player1", 100),
+            ("player1", 50),
+            ("player2", 80),
+            ("player1", 150),
+        ] {
+            let session_id =
+                open_game_session(&mut app, &addr, "tetris", player);
+            app.execute_contract(
+                Addr::unchecked("admin1"),
+                addr.clone(),
+                &ExecuteMsg::AddTopUser {
+                    game_id: "tetris".to_string(),
+                    user: test_user(player, score, player.to_string()),
+                    session_id,
+                },
+                &[],
+            )
+            .unwrap();
+        }
+
+        let resp: ScoreboardListResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::ScoreList {
+                    game_id: "tetris".to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            ranked(&resp),
+            vec![
+                (1, test_user("player1", 150, "player1".to_string())),
+                (2, test_user("player2", 80, "player2".to_string())),
+            ]
+        );
+
+        // the new top score on the full board took the prize pool of the
+        // 4 plays
+        let balance = app
+            .wrap()
+            .query_balance("player1", "aconst")
+            .unwrap()
+            .amount
+            .u128();
+        assert_eq!(balance, 100 - 3 + 4);
+    }
+
+    #[test]
+    fn lower_is_better() {
+        let mut app = funded_app(&["player1", "player2"]);
+        let addr = instantiate_signed_arcade(&mut app);
+        app.execute_contract(
+            Addr::unchecked("admin1"),
+            addr.clone(),
+            &ExecuteMsg::AddGame {
+                game_id: "race".to_string(),
+                name: "Race".to_string(),
+                asset: AssetInfo::Native("aconst".to_string()),
+                price: 1,
+                max_top_score: 2,
+                entry_mode: EntryMode::Multiple,
+                score_order: ScoreOrder::LowerIsBetter,
+            },
+            &[],
+        )
+        .unwrap();
+
+        for (player, time) in [
+            ("player1", 90),
+            ("player2", 60),
+            ("player1", 30),
+            ("player2", 100),
+        ] {
+            let session_id = open_game_session(&mut app, &addr, "race", player);
+            app.execute_contract(
+                Addr::unchecked("admin1"),
+                addr.clone(),
+                &ExecuteMsg::AddTopUser {
+                    game_id: "race".to_string(),
+                    user: test_user(player, time, player.to_string()),
+                    session_id,
+                },
+                &[],
+            )
+            .unwrap();
+        }
+
+        let resp: ScoreboardListResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::ScoreList {
+                    game_id: "race".to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            ranked(&resp),
+            vec![
+                (1, test_user("player1", 30, "player1".to_string())),
+                (2, test_user("player2", 60, "player2".to_string())),
+            ]
+        );
+
+        // the fastest time on the full board took the pool of the 3 plays
+        let balance = app
+            .wrap()
+            .query_balance("player1", "aconst")
+            .unwrap()
+            .amount
+            .u128();
+        assert_eq!(balance, 100 - 2 + 3);
+
+        let resp: PlayerStatsResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::PlayerStats {
+                    game_id: "race".to_string(),
+                    address: "player2".to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(resp.stats.personal_best, Some(Uint64::new(60)));
+    }
+
+    #[test]
+    fn roles() {
+        let mut app = funded_app(&["player1"]);
+        let addr = instantiate_signed_arcade(&mut app);
+        let roles = |app: &App, address: &str| -> Vec<Role> {
+            let resp: RolesResp = app
+                .wrap()
+                .query_wasm_smart(
+                    &addr,
+                    &QueryMsg::Roles {
+                        address: address.to_string(),
+                    },
+                )
+                .unwrap();
+            resp.roles
+        };
+        assert_eq!(roles(&app, "owner"), vec![Role::Owner]);
+        assert_eq!(roles(&app, "admin1"), Role::OPERATIONAL);
+
+        // price changes need the ConfigManager role
+        let update_price = ExecuteMsg::UpdatePrice {
+            game_id: DEFAULT_GAME_ID.to_string(),
+            price: 0,
+        };
+        let err = app
+            .execute_contract(
+                Addr::unchecked("player1"),
+                addr.clone(),
+                &update_price,
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::MissingRole {
+                sender: Addr::unchecked("player1"),
+                role: Role::ConfigManager,
+            },
+            err.downcast().unwrap()
+        );
+
+        // only the owner grants roles
+        let grant = ExecuteMsg::GrantRole {
+            address: "reporter".to_string(),
+            role: Role::ScoreReporter,
+        };
+        let err = app
+            .execute_contract(
+                Addr::unchecked("admin1"),
+                addr.clone(),
+                &grant,
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::MissingRole {
+                sender: Addr::unchecked("admin1"),
+                role: Role::Owner,
+            },
+            err.downcast().unwrap()
+        );
+        app.execute_contract(
+            Addr::unchecked("owner"),
+            addr.clone(),
+            &grant,
+            &[],
+        )
+        .unwrap();
+
+        // a score reporter adds scores but cannot change the price
+        let session_id = open_session(&mut app, &addr, "player1");
+        app.execute_contract(
+            Addr::unchecked("reporter"),
+            addr.clone(),
+            &ExecuteMsg::AddTopUser {
+                game_id: DEFAULT_GAME_ID.to_string(),
+                user: test_user("player1", 10, "player1".to_string()),
+                session_id,
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            Addr::unchecked("reporter"),
+            addr.clone(),
+            &update_price,
+            &[],
+        )
+        .unwrap_err();
+
+        let resp: RoleMembersResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::RoleMembers {
+                    role: Role::ScoreReporter,
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            resp.members,
+            vec![Addr::unchecked("admin1"), Addr::unchecked("reporter")]
+        );
+        let resp: RoleMembersResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::RoleMembers {
+                    role: Role::ScoreReporter,
+                    start_after: Some("admin1".to_string()),
+                    limit: Some(1),
+                },
+            )
+            .unwrap();
+        assert_eq!(resp.members, vec![Addr::unchecked("reporter")]);
+
+        // an operational role makes an admin, who shares the operator revenue
+        let admins = |app: &App| -> Vec<Addr> {
+            let resp: AdminsListResp = app
+                .wrap()
+                .query_wasm_smart(&addr, &QueryMsg::AdminsList {})
+                .unwrap();
+            resp.admins
+        };
+        assert_eq!(
+            admins(&app),
+            vec![Addr::unchecked("admin1"), Addr::unchecked("reporter")]
+        );
+        app.execute_contract(
+            Addr::unchecked("admin1"),
+            addr.clone(),
+            &ExecuteMsg::UpdatePrice {
+                game_id: DEFAULT_GAME_ID.to_string(),
+                price: 4,
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            Addr::unchecked("player1"),
+            addr.clone(),
+            &ExecuteMsg::Play {
+                game_id: DEFAULT_GAME_ID.to_string(),
+                token_id: None,
+            },
+            &coins(4, "aconst"),
+        )
+        .unwrap();
+        let balance = app.wrap().query_balance("reporter", "aconst").unwrap();
+        assert_eq!(balance.amount.u128(), 1);
+
+        let revoke = |address: &str, role| ExecuteMsg::RevokeRole {
+            address: address.to_string(),
+            role,
+        };
+        app.execute_contract(
+            Addr::unchecked("owner"),
+            addr.clone(),
+            &revoke("reporter", Role::ScoreReporter),
+            &[],
+        )
+        .unwrap();
+        assert_eq!(roles(&app, "reporter"), vec![]);
+        assert_eq!(admins(&app), vec![Addr::unchecked("admin1")]);
+
+        // the last admin keeps its last operational role
+        let (last, others) = Role::OPERATIONAL.split_last().unwrap();
+        for role in others {
+            app.execute_contract(
+                Addr::unchecked("owner"),
+                addr.clone(),
+                &revoke("admin1", *role),
+                &[],
+            )
+            .unwrap();
+        }
+        let err = app
+            .execute_contract(
+                Addr::unchecked("owner"),
+                addr.clone(),
+                &revoke("admin1", *last),
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(ContractError::LastAdmin, err.downcast().unwrap());
+
+        // the Owner role moves only by ownership transfer
+        let err = app
+            .execute_contract(
+                Addr::unchecked("owner"),
+                addr.clone(),
+                &ExecuteMsg::GrantRole {
+                    address: "reporter".to_string(),
+                    role: Role::Owner,
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(ContractError::OwnerRole, err.downcast().unwrap());
+    }
+
+    #[test]
+    fn privileged_messages_need_role() {
+        let mut app = funded_app(&["player1"]);
+        let addr = instantiate_signed_arcade(&mut app);
+        let game_id = || DEFAULT_GAME_ID.to_string();
+        let messages = [
+            (
+                ExecuteMsg::AddAdmin {
+                    admins: vec!["player1".to_string()],
+                },
+                Role::Owner,
+            ),
+            (
+                ExecuteMsg::RemoveAdmin {
+                    admin: "admin1".to_string(),
+                },
+                Role::Owner,
+            ),
+            (
+                ExecuteMsg::ProposeOwner {
+                    owner: Some("player1".to_string()),
+                },
+                Role::Owner,
+            ),
+            (
+                ExecuteMsg::GrantRole {
+                    address: "player1".to_string(),
+                    role: Role::ScoreReporter,
+                },
+                Role::Owner,
+            ),
+            (
+                ExecuteMsg::RevokeRole {
+                    address: "admin1".to_string(),
+                    role: Role::ScoreReporter,
+                },
+                Role::Owner,
+            ),
+            (
+                ExecuteMsg::SetPriceBounds {
+                    bounds: Some(PriceBounds { min: 0, max: 0 }),
+                },
+                Role::Owner,
+            ),
+            (
+                ExecuteMsg::UpdatePrice {
+                    game_id: game_id(),
+                    price: 0,
+                },
+                Role::ConfigManager,
+            ),
+            (
+                ExecuteMsg::UpdateConfig {
+                    game_id: game_id(),
+                    name: None,
+                    asset: None,
+                    price: Some(0),
+                    max_top_score: None,
+                },
+                Role::ConfigManager,
+            ),
+            (
+                ExecuteMsg::SetPaymentMode {
+                    mode: PaymentMode::Exact,
+                },
+                Role::ConfigManager,
+            ),
+            (
+                ExecuteMsg::SetAssetPrice {
+                    game_id: game_id(),
+                    asset: AssetInfo::Native("uatom".to_string()),
+                    price: Some(1),
+                },
+                Role::ConfigManager,
+            ),
+            (
+                ExecuteMsg::SetBulkDiscounts {
+                    game_id: game_id(),
+                    discounts: vec![],
+                },
+                Role::ConfigManager,
+            ),
+            (
+                ExecuteMsg::UpdateSessionTtl { blocks: 0 },
+                Role::ConfigManager,
+            ),
+            (
+                ExecuteMsg::RegisterServerKey {
+                    name: "server3".to_string(),
+                    algorithm: KeyAlgorithm::Ed25519,
+                    public_key: ed25519_server().1,
+                },
+                Role::ConfigManager,
+            ),
+            (
+                ExecuteMsg::RemoveServerKey {
+                    name: "server1".to_string(),
+                },
+                Role::ConfigManager,
+            ),
+            (
+                ExecuteMsg::AddGame {
+                    game_id: "tetris".to_string(),
+                    name: "Tetris".to_string(),
+                    asset: AssetInfo::Native("aconst".to_string()),
+                    price: 1,
                     max_top_score: 10,
-                    price_peer_game: 1,
+                    entry_mode: EntryMode::Multiple,
+                    score_order: ScoreOrder::HigherIsBetter,
+                },
+                Role::ConfigManager,
+            ),
+            (
+                ExecuteMsg::RetireGame { game_id: game_id() },
+                Role::ConfigManager,
+            ),
+            (
+                ExecuteMsg::SetSeasonDuration {
+                    game_id: game_id(),
+                    duration: Some(SeasonDuration::Blocks(1)),
+                },
+                Role::ConfigManager,
+            ),
+            (
+                ExecuteMsg::EndSeason { game_id: game_id() },
+                Role::ConfigManager,
+            ),
+            (
+                ExecuteMsg::SetTrophyConfig { config: None },
+                Role::ConfigManager,
+            ),
+            (
+                ExecuteMsg::SetNftPass {
+                    game_id: game_id(),
+                    pass: None,
+                },
+                Role::ConfigManager,
+            ),
+            (
+                ExecuteMsg::AddTopUser {
+                    game_id: game_id(),
+                    user: test_user("player1", 1, "player1".to_string()),
+                    session_id: 1,
+                },
+                Role::ScoreReporter,
+            ),
+            (
+                ExecuteMsg::SweepSurplus {
                     denom: "aconst".to_string(),
+                    recipient: "player1".to_string(),
+                },
+                Role::Treasurer,
+            ),
+            (
+                ExecuteMsg::RefundCredits {
+                    game_id: game_id(),
+                    address: "player1".to_string(),
+                },
+                Role::Treasurer,
+            ),
+            (
+                ExecuteMsg::UpdateRevenueSplit {
+                    revenue_split: revenue_split(0),
+                },
+                Role::Treasurer,
+            ),
+            (
+                ExecuteMsg::SetPayoutSchedule {
+                    game_id: game_id(),
+                    schedule: Some(vec![10000]),
+                },
+                Role::Treasurer,
+            ),
+            (
+                ExecuteMsg::Distribute { game_id: game_id() },
+                Role::Treasurer,
+            ),
+            (ExecuteMsg::PauseGame { game_id: game_id() }, Role::Pauser),
+            (
+                ExecuteMsg::Pause {
+                    features: vec![Feature::Play],
+                    reason: "test".to_string(),
+                },
+                Role::Pauser,
+            ),
+            (
+                ExecuteMsg::Unpause {
+                    features: vec![Feature::Play],
+                },
+                Role::Pauser,
+            ),
+            (ExecuteMsg::ResumeGame { game_id: game_id() }, Role::Pauser),
+        ];
+        for (msg, role) in messages {
+            let err = app
+                .execute_contract(
+                    Addr::unchecked("player1"),
+                    addr.clone(),
+                    &msg,
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                ContractError::MissingRole {
+                    sender: Addr::unchecked("player1"),
+                    role,
+                },
+                err.downcast().unwrap(),
+                "{msg:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn update_config() {
+        let mut app = funded_app(&["player1"]);
+        let addr = instantiate_signed_arcade(&mut app);
+        let update = |app: &mut App, msg: ExecuteMsg| {
+            app.execute_contract(
+                Addr::unchecked("admin1"),
+                addr.clone(),
+                &msg,
+                &[],
+            )
+            .map_err(|err| err.downcast::<ContractError>().unwrap())
+        };
+        let config = |name: Option<&str>, denom: Option<&str>, price, max| {
+            ExecuteMsg::UpdateConfig {
+                game_id: DEFAULT_GAME_ID.to_string(),
+                name: name.map(str::to_string),
+                asset: denom.map(|denom| AssetInfo::Native(denom.to_string())),
+                price,
+                max_top_score: max,
+            }
+        };
+
+        assert_eq!(
+            update(&mut app, config(Some(" "), None, None, None)).unwrap_err(),
+            ContractError::EmptyGameName
+        );
+        assert_eq!(
+            update(&mut app, config(None, None, None, Some(0))).unwrap_err(),
+            ContractError::ZeroBoardSize
+        );
+
+        // the owner bounds the prices admins can set
+        let err = app
+            .execute_contract(
+                Addr::unchecked("owner"),
+                addr.clone(),
+                &ExecuteMsg::SetPriceBounds {
+                    bounds: Some(PriceBounds { min: 10, max: 1 }),
                 },
                 &[],
-                "Pac-Man".to_string(),
-                None,
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::InvalidPriceBounds { min: 10, max: 1 },
+            err.downcast().unwrap()
+        );
+        app.execute_contract(
+            Addr::unchecked("owner"),
+            addr.clone(),
+            &ExecuteMsg::SetPriceBounds {
+                bounds: Some(PriceBounds { min: 1, max: 100 }),
+            },
+            &[],
+        )
+        .unwrap();
+        let out_of_bounds = ContractError::PriceOutOfBounds {
+            price: 0,
+            min: 1,
+            max: 100,
+        };
+        assert_eq!(
+            update(&mut app, config(None, None, Some(0), None)).unwrap_err(),
+            out_of_bounds
+        );
+        assert_eq!(
+            update(
+                &mut app,
+                ExecuteMsg::UpdatePrice {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    price: 0,
+                }
+            )
+            .unwrap_err(),
+            out_of_bounds
+        );
+
+        // a shrunk board keeps its best entries
+        for score in [10, 30, 20] {
+            let session_id = open_session(&mut app, &addr, "player1");
+            update(
+                &mut app,
+                ExecuteMsg::AddTopUser {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    user: test_user("player1", score, "player1".to_string()),
+                    session_id,
+                },
+            )
+            .unwrap();
+        }
+        update(
+            &mut app,
+            config(Some("Ms. Pac-Man"), None, Some(50), Some(2)),
+        )
+        .unwrap();
+        let resp: GameResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::Game {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(resp.game.name, "Ms. Pac-Man");
+        assert_eq!(resp.game.price, 50);
+        assert_eq!(resp.game.max_top_scores, 2);
+        let resp: ScoreboardListResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::ScoreList {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        let scores: Vec<_> = resp
+            .scores
+            .iter()
+            .map(|score| score.entry.user.score.u64())
+            .collect();
+        assert_eq!(scores, vec![30, 20]);
+
+        // the prize pool holds the plays in the current denom
+        assert_eq!(
+            update(&mut app, config(None, Some("uatom"), None, None))
+                .unwrap_err(),
+            ContractError::PrizePoolNotEmpty {
+                game_id: DEFAULT_GAME_ID.to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn ownership_transfer() {
+        let mut app = App::default();
+        let addr = instantiate_signed_arcade(&mut app);
+        let ownership = |app: &App| -> OwnershipResp {
+            app.wrap()
+                .query_wasm_smart(&addr, &QueryMsg::Ownership {})
+                .unwrap()
+        };
+
+        app.execute_contract(
+            Addr::unchecked("owner"),
+            addr.clone(),
+            &ExecuteMsg::ProposeOwner {
+                owner: Some("new_owner".to_string()),
+            },
+            &[],
+        )
+        .unwrap();
+        // nothing changes until the proposed owner accepts
+        assert_eq!(
+            ownership(&app),
+            OwnershipResp {
+                owner: Addr::unchecked("owner"),
+                pending_owner: Some(Addr::unchecked("new_owner")),
+            }
+        );
+        let err = app
+            .execute_contract(
+                Addr::unchecked("admin1"),
+                addr.clone(),
+                &ExecuteMsg::AcceptOwnership {},
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::NotPendingOwner {
+                sender: Addr::unchecked("admin1")
+            },
+            err.downcast().unwrap()
+        );
+
+        app.execute_contract(
+            Addr::unchecked("new_owner"),
+            addr.clone(),
+            &ExecuteMsg::AcceptOwnership {},
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            ownership(&app),
+            OwnershipResp {
+                owner: Addr::unchecked("new_owner"),
+                pending_owner: None,
+            }
+        );
+        let err = app
+            .execute_contract(
+                Addr::unchecked("owner"),
+                addr.clone(),
+                &ExecuteMsg::ProposeOwner { owner: None },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::MissingRole {
+                sender: Addr::unchecked("owner"),
+                role: Role::Owner,
+            },
+            err.downcast().unwrap()
+        );
+    }
+
+    #[test]
+    fn remove_admins() {
+        let mut app = App::default();
+        let addr = instantiate_signed_arcade(&mut app);
+        let admins = |app: &App| -> Vec<Addr> {
+            let resp: AdminsListResp = app
+                .wrap()
+                .query_wasm_smart(&addr, &QueryMsg::AdminsList {})
+                .unwrap();
+            resp.admins
+        };
+
+        // the last admin cannot leave
+        let err = app
+            .execute_contract(
+                Addr::unchecked("admin1"),
+                addr.clone(),
+                &ExecuteMsg::Leave {},
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(ContractError::LastAdmin, err.downcast().unwrap());
+
+        // known admins are not added twice
+        app.execute_contract(
+            Addr::unchecked("owner"),
+            addr.clone(),
+            &ExecuteMsg::AddAdmin {
+                admins: vec![
+                    "admin1".to_string(),
+                    "admin2".to_string(),
+                    "admin2".to_string(),
+                ],
+            },
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            admins(&app),
+            vec![Addr::unchecked("admin1"), Addr::unchecked("admin2")]
+        );
+
+        app.execute_contract(
+            Addr::unchecked("owner"),
+            addr.clone(),
+            &ExecuteMsg::RemoveAdmin {
+                admin: "admin1".to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+        assert_eq!(admins(&app), vec![Addr::unchecked("admin2")]);
+        let resp: RolesResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::Roles {
+                    address: "admin1".to_string(),
+                },
             )
             .unwrap();
+        assert_eq!(resp.roles, vec![]);
+
+        let err = app
+            .execute_contract(
+                Addr::unchecked("owner"),
+                addr.clone(),
+                &ExecuteMsg::RemoveAdmin {
+                    admin: "admin1".to_string(),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::NotAdmin {
+                address: Addr::unchecked("admin1")
+            },
+            err.downcast().unwrap()
+        );
+        let err = app
+            .execute_contract(
+                Addr::unchecked("owner"),
+                addr.clone(),
+                &ExecuteMsg::RemoveAdmin {
+                    admin: "admin2".to_string(),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(ContractError::LastAdmin, err.downcast().unwrap());
+    }
+
+    #[test]
+    fn emergency_pause() {
+        let mut app = funded_app(&["player1"]);
+        let addr = instantiate_signed_arcade(&mut app);
+        let execute = |app: &mut App, sender: &str, msg: ExecuteMsg| {
+            app.execute_contract(
+                Addr::unchecked(sender),
+                addr.clone(),
+                &msg,
+                &[],
+            )
+            .map_err(|err| err.downcast::<ContractError>().unwrap())
+        };
+        let session_id = open_session(&mut app, &addr, "player1");
+        app.execute_contract(
+            Addr::unchecked("player1"),
+            addr.clone(),
+            &ExecuteMsg::FundPrizePool {
+                game_id: DEFAULT_GAME_ID.to_string(),
+            },
+            &coins(50, "aconst"),
+        )
+        .unwrap();
+        execute(
+            &mut app,
+            "admin1",
+            ExecuteMsg::SetPayoutSchedule {
+                game_id: DEFAULT_GAME_ID.to_string(),
+                schedule: Some(vec![10000]),
+            },
+        )
+        .unwrap();
 
-        let resp: GameCounterResp = app
+        execute(
+            &mut app,
+            "admin1",
+            ExecuteMsg::Pause {
+                features: vec![
+                    Feature::Play,
+                    Feature::ScoreSubmission,
+                    Feature::Payouts,
+                ],
+                reason: "leaked server key".to_string(),
+            },
+        )
+        .unwrap();
+        let resp: PauseStateResp = app
             .wrap()
-            .query_wasm_smart(&addr, &QueryMsg::GameCounter {})
+            .query_wasm_smart(&addr, &QueryMsg::PauseState {})
             .unwrap();
-        assert_eq!(resp, GameCounterResp { game_counter: 0 });
+        assert_eq!(resp.paused.len(), 3);
+        assert_eq!(resp.paused[0].reason, "leaked server key");
 
-        let _resp = app
+        let err = app
             .execute_contract(
-                Addr::unchecked("user"),
+                Addr::unchecked("player1"),
                 addr.clone(),
-                &ExecuteMsg::Play {},
-                &coins(5, "aconst"),
+                &ExecuteMsg::Play {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    token_id: None,
+                },
+                &coins(1, "aconst"),
             )
-            .unwrap();
+            .unwrap_err();
+        assert_eq!(
+            ContractError::Paused {
+                feature: Feature::Play,
+                reason: "leaked server key".to_string(),
+            },
+            err.downcast().unwrap()
+        );
+        let add_score = ExecuteMsg::AddTopUser {
+            game_id: DEFAULT_GAME_ID.to_string(),
+            user: test_user("player1", 10, "player1".to_string()),
+            session_id,
+        };
+        assert_eq!(
+            execute(&mut app, "admin1", add_score.clone()).unwrap_err(),
+            ContractError::Paused {
+                feature: Feature::ScoreSubmission,
+                reason: "leaked server key".to_string(),
+            }
+        );
+        execute(
+            &mut app,
+            "admin1",
+            ExecuteMsg::Unpause {
+                features: vec![Feature::ScoreSubmission],
+            },
+        )
+        .unwrap();
+        execute(&mut app, "admin1", add_score).unwrap();
 
-        let resp: GameCounterResp = app
+        // prizes are held while payouts are paused
+        let resp = execute(
+            &mut app,
+            "admin1",
+            ExecuteMsg::Distribute {
+                game_id: DEFAULT_GAME_ID.to_string(),
+            },
+        )
+        .unwrap();
+        assert!(
+            resp.has_event(&Event::new("wasm").add_attribute("held", "true"))
+        );
+        let held: HeldPayoutsResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::HeldPayouts {
+                    address: "player1".to_string(),
+                },
+            )
+            .unwrap();
+        // the sponsored 50 and the pool share of the play
+        assert_eq!(
+            held.payouts,
+            vec![Asset::new(AssetInfo::Native("aconst".to_string()), 51)]
+        );
+        let resp: SurplusResp = app
             .wrap()
-            .query_wasm_smart(&addr, &QueryMsg::GameCounter {})
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::Surplus {
+                    denom: "aconst".to_string(),
+                },
+            )
             .unwrap();
-        assert_eq!(resp, GameCounterResp { game_counter: 1 })
+        assert_eq!(resp.surplus, 0);
+        assert_eq!(
+            execute(&mut app, "player1", ExecuteMsg::ClaimHeldPayouts {})
+                .unwrap_err(),
+            ContractError::Paused {
+                feature: Feature::Payouts,
+                reason: "leaked server key".to_string(),
+            }
+        );
+
+        execute(
+            &mut app,
+            "admin1",
+            ExecuteMsg::Unpause {
+                features: vec![Feature::Payouts],
+            },
+        )
+        .unwrap();
+        let before = app.wrap().query_balance("player1", "aconst").unwrap();
+        execute(&mut app, "player1", ExecuteMsg::ClaimHeldPayouts {}).unwrap();
+        let after = app.wrap().query_balance("player1", "aconst").unwrap();
+        assert_eq!(after.amount.u128(), before.amount.u128() + 51);
+        assert_eq!(
+            execute(&mut app, "player1", ExecuteMsg::ClaimHeldPayouts {})
+                .unwrap_err(),
+            ContractError::NoHeldPayouts {
+                recipient: Addr::unchecked("player1")
+            }
+        );
     }
 
     #[test]
-    fn instantiation() {
-        let mut app = App::default();
+    fn play_payment_modes() {
+        let mut app = funded_app(&["player1"]);
+        let addr = instantiate_signed_arcade(&mut app);
+        let play = |app: &mut App, funds: &[Coin]| {
+            app.execute_contract(
+                Addr::unchecked("player1"),
+                addr.clone(),
+                &ExecuteMsg::Play {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    token_id: None,
+                },
+                funds,
+            )
+            .map_err(|err| err.downcast::<ContractError>().unwrap())
+        };
+        app.execute_contract(
+            Addr::unchecked("admin1"),
+            addr.clone(),
+            &ExecuteMsg::UpdatePrice {
+                game_id: DEFAULT_GAME_ID.to_string(),
+                price: 10,
+            },
+            &[],
+        )
+        .unwrap();
 
-        let code = ContractWrapper::new(execute, instantiate, query);
-        let code_id = app.store_code(Box::new(code));
+        assert_eq!(
+            play(&mut app, &coins(9, "aconst")).unwrap_err(),
+            ContractError::InsufficientPayment {
+                required: 10,
+                sent: 9
+            }
+        );
+        // the change comes back in the same transaction
+        play(&mut app, &coins(15, "aconst")).unwrap();
+        let balance = app.wrap().query_balance("player1", "aconst").unwrap();
+        assert_eq!(balance.amount.u128(), 90);
 
-        let addr = app
-            .instantiate_contract(
-                code_id,
-                Addr::unchecked("owner"),
-                &InstantiateMsg {
-                    arcade: "pacman".to_string(),
-                    admins: vec![],
-                    max_top_score: 10,
-                    price_peer_game: 1,
+        app.execute_contract(
+            Addr::unchecked("admin1"),
+            addr.clone(),
+            &ExecuteMsg::SetPaymentMode {
+                mode: PaymentMode::Exact,
+            },
+            &[],
+        )
+        .unwrap();
+        let resp: PaymentModeResp = app
+            .wrap()
+            .query_wasm_smart(&addr, &QueryMsg::PaymentMode {})
+            .unwrap();
+        assert_eq!(resp.mode, PaymentMode::Exact);
+        assert_eq!(
+            play(&mut app, &coins(15, "aconst")).unwrap_err(),
+            ContractError::Overpayment {
+                required: 10,
+                sent: 15
+            }
+        );
+        play(&mut app, &coins(10, "aconst")).unwrap();
+    }
+
+    #[test]
+    fn prepaid_credits() {
+        let mut app = funded_app(&["player1"]);
+        let addr = instantiate_signed_arcade(&mut app);
+        let execute =
+            |app: &mut App, sender: &str, msg: ExecuteMsg, funds: &[Coin]| {
+                app.execute_contract(
+                    Addr::unchecked(sender),
+                    addr.clone(),
+                    &msg,
+                    funds,
+                )
+                .map_err(|err| err.downcast::<ContractError>().unwrap())
+            };
+        let credits = |app: &App| {
+            let resp: CreditsResp = app
+                .wrap()
+                .query_wasm_smart(
+                    &addr,
+                    &QueryMsg::Credits {
+                        game_id: DEFAULT_GAME_ID.to_string(),
+                        address: "player1".to_string(),
+                    },
+                )
+                .unwrap();
+            resp.credits
+        };
+        let balance = |app: &App, address: &str| {
+            app.wrap()
+                .query_balance(address, "aconst")
+                .unwrap()
+                .amount
+                .u128()
+        };
+        let play = || ExecuteMsg::Play {
+            game_id: DEFAULT_GAME_ID.to_string(),
+            token_id: None,
+        };
+        let buy = |count| ExecuteMsg::BuyCredits {
+            game_id: DEFAULT_GAME_ID.to_string(),
+            count,
+        };
+        execute(
+            &mut app,
+            "admin1",
+            ExecuteMsg::UpdatePrice {
+                game_id: DEFAULT_GAME_ID.to_string(),
+                price: 10,
+            },
+            &[],
+        )
+        .unwrap();
+        execute(
+            &mut app,
+            "admin1",
+            ExecuteMsg::SetBulkDiscounts {
+                game_id: DEFAULT_GAME_ID.to_string(),
+                discounts: vec![BulkDiscount {
+                    min_count: 5,
+                    discount_bps: 1000,
+                }],
+            },
+            &[],
+        )
+        .unwrap();
+
+        assert_eq!(
+            execute(&mut app, "player1", buy(0), &coins(10, "aconst"))
+                .unwrap_err(),
+            ContractError::ZeroCredits
+        );
+        // five games for 45 after the 10% discount, the rest is sent back
+        execute(&mut app, "player1", buy(5), &coins(50, "aconst")).unwrap();
+        assert_eq!(balance(&app, "player1"), 55);
+        assert_eq!(credits(&app), Credits { count: 5, paid: 45 });
+        // escrowed credits are not surplus
+        let resp: SurplusResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::Surplus {
                     denom: "aconst".to_string(),
                 },
-                &[],
-                "Contract",
-                None,
             )
             .unwrap();
+        assert_eq!(resp.surplus, 0);
 
-        let resp: AdminsListResp = app
-            .wrap()
-            .query_wasm_smart(addr, &QueryMsg::AdminsList {})
-            .unwrap();
-        assert_eq!(resp, AdminsListResp { admins: vec![] });
+        let resp = execute(&mut app, "player1", play(), &[]).unwrap();
+        let wasm = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+        assert!(
+            wasm.attributes
+                .iter()
+                .any(|attr| attr.key == "paid_with_credit"
+                    && attr.value == "true")
+        );
+        assert!(wasm
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "recieved_tokens" && attr.value == "9"));
+        assert_eq!(balance(&app, "player1"), 55);
+        assert_eq!(credits(&app), Credits { count: 4, paid: 36 });
 
-        let addr = app
-            .instantiate_contract(
-                code_id,
-                Addr::unchecked("owner"),
-                &InstantiateMsg {
-                    arcade: "Pac-Man".to_string(),
-                    admins: vec!["admin1".to_owned(), "admin2".to_owned()],
-                    max_top_score: 10,
-                    price_peer_game: 1,
-                    denom: "aconst".to_string(),
+        // attached funds pay for the game instead of a credit
+        execute(&mut app, "player1", play(), &coins(10, "aconst")).unwrap();
+        assert_eq!(credits(&app), Credits { count: 4, paid: 36 });
+
+        execute(
+            &mut app,
+            "admin1",
+            ExecuteMsg::RefundCredits {
+                game_id: DEFAULT_GAME_ID.to_string(),
+                address: "player1".to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+        assert_eq!(balance(&app, "player1"), 81);
+        assert_eq!(credits(&app), Credits::default());
+        assert_eq!(
+            execute(&mut app, "player1", play(), &[]).unwrap_err(),
+            ContractError::InsufficientPayment {
+                required: 10,
+                sent: 0
+            }
+        );
+        assert_eq!(
+            execute(
+                &mut app,
+                "admin1",
+                ExecuteMsg::RefundCredits {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    address: "player1".to_string(),
                 },
                 &[],
-                "Contract 2",
-                None,
             )
-            .unwrap();
+            .unwrap_err(),
+            ContractError::NoCredits {
+                address: Addr::unchecked("player1"),
+                game_id: DEFAULT_GAME_ID.to_string(),
+            }
+        );
 
-        let resp: AdminsListResp = app
-            .wrap()
-            .query_wasm_smart(&addr, &QueryMsg::AdminsList {})
+        // free credits still stop at the largest count
+        execute(
+            &mut app,
+            "admin1",
+            ExecuteMsg::UpdatePrice {
+                game_id: DEFAULT_GAME_ID.to_string(),
+                price: 0,
+            },
+            &[],
+        )
+        .unwrap();
+        execute(&mut app, "player1", buy(u32::MAX), &[]).unwrap();
+        assert_eq!(
+            execute(&mut app, "player1", buy(1), &[]).unwrap_err(),
+            ContractError::CreditsOverflow {
+                address: Addr::unchecked("player1"),
+                game_id: DEFAULT_GAME_ID.to_string(),
+            }
+        );
+    }
+
+    // bare CW20 token holding balances set at instantiation
+    mod mock_cw20 {
+        use cosmwasm_std::{
+            to_json_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo,
+            Response, StdError, StdResult, Uint128,
+        };
+        use cw20::{
+            BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg,
+        };
+        use cw_storage_plus::Map;
+
+        const BALANCES: Map<&Addr, Uint128> = Map::new("balances");
+
+        fn update(
+            deps: &mut DepsMut,
+            address: &Addr,
+            change: impl FnOnce(Uint128) -> StdResult<Uint128>,
+        ) -> StdResult<()> {
+            let balance = BALANCES
+                .may_load(deps.storage, address)?
+                .unwrap_or_default();
+            BALANCES.save(deps.storage, address, &change(balance)?)
+        }
+
+        fn transfer(
+            deps: &mut DepsMut,
+            from: &Addr,
+            to: &str,
+            amount: Uint128,
+        ) -> StdResult<()> {
+            update(deps, from, |balance| Ok(balance.checked_sub(amount)?))?;
+            let to = deps.api.addr_validate(to)?;
+            update(deps, &to, |balance| Ok(balance + amount))
+        }
+
+        pub fn instantiate(
+            deps: DepsMut,
+            _env: Env,
+            _info: MessageInfo,
+            balances: Vec<(String, Uint128)>,
+        ) -> StdResult<Response> {
+            for (address, amount) in balances {
+                let address = deps.api.addr_validate(&address)?;
+                BALANCES.save(deps.storage, &address, &amount)?;
+            }
+            Ok(Response::new())
+        }
+
+        pub fn execute(
+            mut deps: DepsMut,
+            _env: Env,
+            info: MessageInfo,
+            msg: Cw20ExecuteMsg,
+        ) -> StdResult<Response> {
+            match msg {
+                Cw20ExecuteMsg::Transfer { recipient, amount } => {
+                    transfer(&mut deps, &info.sender, &recipient, amount)?;
+                    Ok(Response::new())
+                }
+                Cw20ExecuteMsg::Send {
+                    contract,
+                    amount,
+                    msg,
+                } => {
+                    transfer(&mut deps, &info.sender, &contract, amount)?;
+                    let receive = Cw20ReceiveMsg {
+                        sender: info.sender.to_string(),
+                        amount,
+                        msg,
+                    };
+                    Ok(Response::new()
+                        .add_message(receive.into_cosmos_msg(contract)?))
+                }
+                Cw20ExecuteMsg::Burn { amount } => {
+                    update(&mut deps, &info.sender, |balance| {
+                        Ok(balance.checked_sub(amount)?)
+                    })?;
+                    Ok(Response::new())
+                }
+                _ => Err(StdError::generic_err("unsupported")),
+            }
+        }
+
+        pub fn query(
+            deps: Deps,
+            _env: Env,
+            msg: Cw20QueryMsg,
+        ) -> StdResult<Binary> {
+            match msg {
+                Cw20QueryMsg::Balance { address } => {
+                    let address = deps.api.addr_validate(&address)?;
+                    let balance = BALANCES
+                        .may_load(deps.storage, &address)?
+                        .unwrap_or_default();
+                    to_json_binary(&BalanceResponse { balance })
+                }
+                _ => Err(StdError::generic_err("unsupported")),
+            }
+        }
+    }
+
+    fn instantiate_cw20(app: &mut App, balances: &[(&str, u128)]) -> Addr {
+        let code = ContractWrapper::new(
+            mock_cw20::execute,
+            mock_cw20::instantiate,
+            mock_cw20::query,
+        );
+        let code_id = app.store_code(Box::new(code));
+        let balances: Vec<_> = balances
+            .iter()
+            .map(|(address, amount)| {
+                (address.to_string(), Uint128::new(*amount))
+            })
+            .collect();
+        app.instantiate_contract(
+            code_id,
+            Addr::unchecked("owner"),
+            &balances,
+            &[],
+            "Token",
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn cw20_game() {
+        let mut app = funded_app(&[]);
+        let addr = instantiate_signed_arcade(&mut app);
+        let token = instantiate_cw20(&mut app, &[("player1", 1000)]);
+        let other = instantiate_cw20(&mut app, &[("player1", 1000)]);
+        let game_id = || "tetris".to_string();
+        let send = |app: &mut App, token: &Addr, amount, msg: ReceiveMsg| {
+            app.execute_contract(
+                Addr::unchecked("player1"),
+                token.clone(),
+                &cw20::Cw20ExecuteMsg::Send {
+                    contract: addr.to_string(),
+                    amount: Uint128::new(amount),
+                    msg: to_json_binary(&msg).unwrap(),
+                },
+                &[],
+            )
+            .map_err(|err| err.downcast::<ContractError>().unwrap())
+        };
+        let balance = |app: &App, address: &str| {
+            let resp: cw20::BalanceResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    &token,
+                    &cw20::Cw20QueryMsg::Balance {
+                        address: address.to_string(),
+                    },
+                )
+                .unwrap();
+            resp.balance.u128()
+        };
+        let prize_pool = |app: &App| {
+            let resp: PrizePoolResp = app
+                .wrap()
+                .query_wasm_smart(
+                    &addr,
+                    &QueryMsg::PrizePool { game_id: game_id() },
+                )
+                .unwrap();
+            resp.prize_pool
+        };
+        app.execute_contract(
+            Addr::unchecked("admin1"),
+            addr.clone(),
+            &ExecuteMsg::AddGame {
+                game_id: game_id(),
+                name: "Tetris".to_string(),
+                asset: AssetInfo::Cw20(token.clone()),
+                price: 10,
+                max_top_score: 10,
+                entry_mode: EntryMode::Multiple,
+                score_order: ScoreOrder::HigherIsBetter,
+            },
+            &[],
+        )
+        .unwrap();
+
+        // half of the price goes to the pool and half to admin1, the
+        // change comes back in tokens
+        let resp = send(
+            &mut app,
+            &token,
+            15,
+            ReceiveMsg::Play {
+                game_id: game_id(),
+                token_id: None,
+            },
+        )
+        .unwrap();
+        let session_id: u64 = resp
+            .events
+            .iter()
+            .flat_map(|event| event.attributes.iter())
+            .find(|attr| attr.key == "session_id")
+            .unwrap()
+            .value
+            .parse()
             .unwrap();
+        assert_eq!(balance(&app, "player1"), 990);
+        assert_eq!(balance(&app, "admin1"), 5);
+        assert_eq!(balance(&app, addr.as_str()), 5);
+        assert_eq!(prize_pool(&app), 5);
+
+        send(
+            &mut app,
+            &token,
+            20,
+            ReceiveMsg::FundPrizePool { game_id: game_id() },
+        )
+        .unwrap();
+        assert_eq!(prize_pool(&app), 25);
 
         assert_eq!(
-            resp,
-            AdminsListResp {
-                admins: vec![
-                    Addr::unchecked("admin1"),
-                    Addr::unchecked("admin2"),
-                ],
+            send(
+                &mut app,
+                &other,
+                10,
+                ReceiveMsg::Play {
+                    game_id: game_id(),
+                    token_id: None,
+                },
+            )
+            .unwrap_err(),
+            ContractError::UnexpectedDenom {
+                denom: other.to_string()
             }
         );
-    }
+        let err = app
+            .execute_contract(
+                Addr::unchecked("player1"),
+                addr.clone(),
+                &ExecuteMsg::FundPrizePool { game_id: game_id() },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::Payment(cw_utils::PaymentError::NoFunds {})
+        );
 
-    fn test_user(name: &str, score: u16, addr: String) -> User {
-        User {
-            name: name.to_string(),
-            address: Addr::unchecked(addr),
-            score: Reverse(score),
+        // prizes are paid out with CW20 transfers
+        for msg in [
+            ExecuteMsg::AddTopUser {
+                game_id: game_id(),
+                user: test_user("player1", 100, "player1".to_string()),
+                session_id,
+            },
+            ExecuteMsg::SetPayoutSchedule {
+                game_id: game_id(),
+                schedule: Some(vec![10000]),
+            },
+            ExecuteMsg::Distribute { game_id: game_id() },
+        ] {
+            app.execute_contract(
+                Addr::unchecked("admin1"),
+                addr.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
         }
+        assert_eq!(balance(&app, "player1"), 970 + 25);
+        assert_eq!(prize_pool(&app), 0);
+        let resp: TotalDistributionResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::TotalDistributed { game_id: game_id() },
+            )
+            .unwrap();
+        assert_eq!(resp.total_distributed, 25);
     }
 
     #[test]
-    fn write_score() {
+    fn multiple_assets() {
         let mut app = App::new(|router, _, storage| {
             router
                 .bank
                 .init_balance(
                     storage,
-                    &Addr::unchecked("arcade"),
-                    coins(100000, "aconst"),
+                    &Addr::unchecked("player1"),
+                    vec![
+                        coin(100, "aconst"),
+                        coin(100, "uatom"),
+                        coin(100, "uosmo"),
+                    ],
                 )
-                .unwrap()
+                .unwrap();
         });
+        let addr = instantiate_signed_arcade(&mut app);
+        let aconst = || AssetInfo::Native("aconst".to_string());
+        let uatom = || AssetInfo::Native("uatom".to_string());
+        let execute =
+            |app: &mut App, sender: &str, msg: ExecuteMsg, funds: &[Coin]| {
+                app.execute_contract(
+                    Addr::unchecked(sender),
+                    addr.clone(),
+                    &msg,
+                    funds,
+                )
+                .map_err(|err| err.downcast::<ContractError>().unwrap())
+            };
+        let play = || ExecuteMsg::Play {
+            game_id: DEFAULT_GAME_ID.to_string(),
+            token_id: None,
+        };
+        let set_price = |asset, price| ExecuteMsg::SetAssetPrice {
+            game_id: DEFAULT_GAME_ID.to_string(),
+            asset,
+            price,
+        };
+        let prices = |app: &App| {
+            let resp: PricesResp = app
+                .wrap()
+                .query_wasm_smart(
+                    &addr,
+                    &QueryMsg::Prices {
+                        game_id: DEFAULT_GAME_ID.to_string(),
+                    },
+                )
+                .unwrap();
+            resp.prices
+        };
 
-        let code = ContractWrapper::new(execute, instantiate, query);
-        let code_id = app.store_code(Box::new(code));
-        let max = 1;
-        let addr = app
-            .instantiate_contract(
-                code_id,
-                Addr::unchecked("wotori"),
-                &InstantiateMsg {
-                    admins: vec!["wotori".to_string()],
-                    arcade: "Pac-Man".to_string(),
-                    max_top_score: max,
-                    price_peer_game: 1,
-                    denom: "aconst".to_string(),
+        execute(&mut app, "admin1", set_price(uatom(), Some(4)), &[]).unwrap();
+        assert_eq!(
+            prices(&app),
+            vec![Asset::new(aconst(), 1), Asset::new(uatom(), 4)]
+        );
+        // the game asset is priced with `UpdatePrice`
+        assert_eq!(
+            execute(&mut app, "admin1", set_price(aconst(), Some(2)), &[])
+                .unwrap_err(),
+            ContractError::AssetAccepted {
+                game_id: DEFAULT_GAME_ID.to_string(),
+                asset: "aconst".to_string(),
+            }
+        );
+
+        // half of the uatom price goes to its own pool
+        let session_id = {
+            let resp = execute(&mut app, "player1", play(), &coins(4, "uatom"))
+                .unwrap();
+            let wasm = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+            assert!(wasm
+                .attributes
+                .iter()
+                .any(|attr| attr.key == "asset" && attr.value == "uatom"));
+            wasm.attributes
+                .iter()
+                .find(|attr| attr.key == "session_id")
+                .unwrap()
+                .value
+                .parse()
+                .unwrap()
+        };
+        execute(&mut app, "player1", play(), &coins(1, "aconst")).unwrap();
+        assert_eq!(
+            execute(&mut app, "player1", play(), &coins(4, "uosmo"))
+                .unwrap_err(),
+            ContractError::InsufficientPayment {
+                required: 1,
+                sent: 0
+            }
+        );
+        execute(
+            &mut app,
+            "player1",
+            ExecuteMsg::FundPrizePool {
+                game_id: DEFAULT_GAME_ID.to_string(),
+            },
+            &coins(10, "uatom"),
+        )
+        .unwrap();
+        let resp: PrizePoolResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::PrizePool {
+                    game_id: DEFAULT_GAME_ID.to_string(),
                 },
-                &[],
-                "cw-arcade",
-                None,
-            )
-            .unwrap();
-
-        let user1 = test_user("user1", 299, "test".to_string());
-        let _resp = app
-            .execute_contract(
-                Addr::unchecked("wotori"),
-                addr.clone(),
-                &ExecuteMsg::AddTopUser { user: user1.clone() },
-                &[],
             )
             .unwrap();
-
-        let user2 = test_user("user2", 300, "test".to_string());
-        let _resp = app
-            .execute_contract(
-                Addr::unchecked("wotori"),
-                addr.clone(),
-                &ExecuteMsg::AddTopUser { user: user2.clone() },
-                &[],
+        assert_eq!(resp.prize_pool, 1);
+        assert_eq!(
+            resp.pools,
+            vec![Asset::new(aconst(), 1), Asset::new(uatom(), 12)]
+        );
+        let resp: SurplusResp = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::Surplus {
+                    denom: "uatom".to_string(),
+                },
             )
             .unwrap();
+        assert_eq!(resp.surplus, 0);
 
-        let resp: ScoreboardListResp = app
+        // a payout pays out every pool
+        for msg in [
+            ExecuteMsg::AddTopUser {
+                game_id: DEFAULT_GAME_ID.to_string(),
+                user: test_user("player1", 100, "player1".to_string()),
+                session_id,
+            },
+            ExecuteMsg::SetPayoutSchedule {
+                game_id: DEFAULT_GAME_ID.to_string(),
+                schedule: Some(vec![10000]),
+            },
+            ExecuteMsg::Distribute {
+                game_id: DEFAULT_GAME_ID.to_string(),
+            },
+        ] {
+            execute(&mut app, "admin1", msg, &[]).unwrap();
+        }
+        let balance = app.wrap().query_balance("player1", "uatom").unwrap();
+        assert_eq!(balance.amount.u128(), 100 - 4 - 10 + 12);
+        let resp: TotalDistributionResp = app
             .wrap()
-            .query_wasm_smart(&addr, &QueryMsg::ScoreList {})
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::TotalDistributed {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                },
+            )
             .unwrap();
-
+        assert_eq!(resp.total_distributed, 1);
         assert_eq!(
-            resp,
-            ScoreboardListResp {
-                scores: vec![user2]
-            }
+            resp.per_asset,
+            vec![Asset::new(aconst(), 1), Asset::new(uatom(), 12)]
         );
 
-        assert_eq!(resp.scores.len(), usize::from(max))
+        execute(&mut app, "admin1", set_price(uatom(), None), &[]).unwrap();
+        assert_eq!(prices(&app), vec![Asset::new(aconst(), 1)]);
     }
 
-    #[test]
-    fn unauthorized() {
-        let mut app = App::default();
+    // cw721-base with trophy metadata as the token extension
+    mod trophy_nft {
+        use cosmwasm_std::{
+            Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdResult,
+        };
+        use cw721_base::{
+            ContractError, Cw721Contract, ExecuteMsg, InstantiateMsg, QueryMsg,
+        };
+
+        use crate::state::TrophyMetadata;
+
+        type Contract<'a> =
+            Cw721Contract<'a, TrophyMetadata, Empty, Empty, Empty>;
+
+        pub fn instantiate(
+            deps: DepsMut,
+            env: Env,
+            info: MessageInfo,
+            msg: InstantiateMsg,
+        ) -> StdResult<Response> {
+            Contract::default().instantiate(deps, env, info, msg)
+        }
+
+        pub fn execute(
+            deps: DepsMut,
+            env: Env,
+            info: MessageInfo,
+            msg: ExecuteMsg<TrophyMetadata, Empty>,
+        ) -> Result<Response, ContractError> {
+            Contract::default().execute(deps, env, info, msg)
+        }
 
-        let code = ContractWrapper::new(execute, instantiate, query);
+        pub fn query(
+            deps: Deps,
+            env: Env,
+            msg: QueryMsg<Empty>,
+        ) -> StdResult<Binary> {
+            Contract::default().query(deps, env, msg)
+        }
+    }
+
+    fn instantiate_cw721(app: &mut App, minter: &Addr) -> Addr {
+        let code = ContractWrapper::new(
+            trophy_nft::execute,
+            trophy_nft::instantiate,
+            trophy_nft::query,
+        );
         let code_id = app.store_code(Box::new(code));
+        app.instantiate_contract(
+            code_id,
+            Addr::unchecked("owner"),
+            &cw721_base::InstantiateMsg {
+                name: "Trophies".to_string(),
+                symbol: "TROPHY".to_string(),
+                minter: minter.to_string(),
+            },
+            &[],
+            "Trophies",
+            None,
+        )
+        .unwrap()
+    }
 
-        let addr = app
-            .instantiate_contract(
-                code_id,
-                Addr::unchecked("owner"),
-                &InstantiateMsg {
-                    admins: vec![],
-                    arcade: "Pac-Man".to_string(),
-                    max_top_score: 10,
-                    price_peer_game: 1,
-                    denom: "aconst".to_string(),
+    #[test]
+    fn trophies() {
+        let mut app = funded_app(&["player1", "player2"]);
+        let addr = instantiate_signed_arcade(&mut app);
+        let nft = instantiate_cw721(&mut app, &addr);
+        let minted = |resp: &cw_multi_test::AppResponse| {
+            resp.events
+                .iter()
+                .flat_map(|event| event.attributes.iter())
+                .filter(|attr| attr.key == "trophy")
+                .map(|attr| attr.value.clone())
+                .collect::<Vec<_>>()
+        };
+        let add_score = |app: &mut App, player: &str, score| {
+            let session_id = open_session(app, &addr, player);
+            app.execute_contract(
+                Addr::unchecked("admin1"),
+                addr.clone(),
+                &ExecuteMsg::AddTopUser {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    user: test_user(player, score, player.to_string()),
+                    session_id,
                 },
                 &[],
-                "Contract",
-                None,
             )
-            .unwrap();
+            .unwrap()
+        };
+        let token = |app: &App, token_id: &str| {
+            let token: AllNftInfoResponse<TrophyMetadata> = app
+                .wrap()
+                .query_wasm_smart(
+                    &nft,
+                    &cw721_base::QueryMsg::<Empty>::AllNftInfo {
+                        token_id: token_id.to_string(),
+                        include_expired: None,
+                    },
+                )
+                .unwrap();
+            (token.access.owner, token.info.extension)
+        };
 
-        let err = app
-            .execute_contract(
-                Addr::unchecked("user"),
-                addr,
-                &ExecuteMsg::AddAdmin {
-                    admins: vec!["user".to_owned()],
-                },
-                &[],
-            )
-            .unwrap_err();
+        // nothing is minted until a trophy contract is configured
+        let resp = add_score(&mut app, "player1", 10);
+        assert!(minted(&resp).is_empty());
 
-        assert_eq!(
-            ContractError::Unauthorized {
-                sender: Addr::unchecked("user")
+        app.execute_contract(
+            Addr::unchecked("admin1"),
+            addr.clone(),
+            &ExecuteMsg::SetTrophyConfig {
+                config: Some(TrophyConfig {
+                    contract: nft.clone(),
+                    season_places: 2,
+                }),
             },
-            err.downcast().unwrap()
-        );
-    }
+            &[],
+        )
+        .unwrap();
 
-    #[test]
-    fn play_with_pay() {
-        let mut app = App::new(|router, _, storage| {
-            router
-                .bank
-                .init_balance(
-                    storage,
-                    &Addr::unchecked("user1"),
-                    coins(100000, "aconst"),
-                )
-                .unwrap()
-        });
+        // player1 was already on the board and stays #1
+        let resp = add_score(&mut app, "player1", 100);
+        assert!(minted(&resp).is_empty());
 
-        let code = ContractWrapper::new(execute, instantiate, query);
-        let code_id = app.store_code(Box::new(code));
+        let resp = add_score(&mut app, "player2", 50);
+        assert_eq!(minted(&resp), vec!["arcade-1"]);
+        assert!(resp.has_event(
+            &Event::new("wasm-arcade_trophy_minted")
+                .add_attribute("token_id", "arcade-1")
+                .add_attribute("owner", "player2")
+                .add_attribute("kind", "FirstEntry")
+                .add_attribute("rank", "2")
+        ));
+        let time = app.block_info().time;
+        let metadata = |kind, score, rank, season_id| TrophyMetadata {
+            kind,
+            game_id: DEFAULT_GAME_ID.to_string(),
+            score: Uint64::new(score),
+            rank,
+            season_id,
+            time,
+        };
+        assert_eq!(
+            token(&app, "arcade-1"),
+            (
+                "player2".to_string(),
+                metadata(TrophyKind::FirstEntry, 50, 2, 1)
+            )
+        );
 
-        let addr = app
-            .instantiate_contract(
-                code_id,
-                Addr::unchecked("owner"),
-                &InstantiateMsg {
-                    arcade: "pacman".to_string(),
-                    admins: vec!["admin1".to_owned(), "admin2".to_owned()],
-                    max_top_score: 1,
-                    price_peer_game: 123,
-                    denom: "aconst".to_string(),
+        let resp = add_score(&mut app, "player2", 200);
+        assert_eq!(minted(&resp), vec!["arcade-2"]);
+        assert_eq!(
+            token(&app, "arcade-2"),
+            (
+                "player2".to_string(),
+                metadata(TrophyKind::TopRank, 200, 1, 1)
+            )
+        );
+
+        // the first two places of the closing season get a trophy
+        let resp = app
+            .execute_contract(
+                Addr::unchecked("admin1"),
+                addr.clone(),
+                &ExecuteMsg::EndSeason {
+                    game_id: DEFAULT_GAME_ID.to_string(),
                 },
                 &[],
-                "Pac-Man Arcade",
-                None,
             )
             .unwrap();
+        assert_eq!(minted(&resp), vec!["arcade-3", "arcade-4"]);
+        assert_eq!(
+            token(&app, "arcade-3"),
+            (
+                "player2".to_string(),
+                metadata(TrophyKind::SeasonPlacement, 200, 1, 1)
+            )
+        );
+        assert_eq!(
+            token(&app, "arcade-4"),
+            (
+                "player1".to_string(),
+                metadata(TrophyKind::SeasonPlacement, 100, 2, 1)
+            )
+        );
+
+        let resp: TrophyConfigResp = app
+            .wrap()
+            .query_wasm_smart(&addr, &QueryMsg::TrophyConfig {})
+            .unwrap();
+        assert_eq!(resp.minted, 4);
 
+        // a failed mint is logged and the score still counts
+        let not_minter = instantiate_cw721(&mut app, &Addr::unchecked("owner"));
         app.execute_contract(
-            Addr::unchecked("user1"),
+            Addr::unchecked("admin1"),
             addr.clone(),
-            &ExecuteMsg::Play {},
-            &coins(333, "aconst"),
+            &ExecuteMsg::SetTrophyConfig {
+                config: Some(TrophyConfig {
+                    contract: not_minter,
+                    season_places: 2,
+                }),
+            },
+            &[],
         )
-            .unwrap();
-
-        let resp: PrizePoolResp = app
+        .unwrap();
+        let resp = add_score(&mut app, "player1", 300);
+        assert!(resp.has_event(
+            &Event::new("wasm-arcade_trophy_mint_failed")
+                .add_attribute("token_id", "arcade-5")
+                .add_attribute("owner", "player1")
+        ));
+        assert!(!resp
+            .events
+            .iter()
+            .any(|event| event.ty == "wasm-arcade_trophy_minted"));
+        assert!(minted(&resp).is_empty());
+        let config: TrophyConfigResp = app
             .wrap()
-            .query_wasm_smart(&addr, &QueryMsg::PrizePool {})
+            .query_wasm_smart(&addr, &QueryMsg::TrophyConfig {})
             .unwrap();
-        assert_eq!(resp, PrizePoolResp { prize_pool: 111 });
-
-        let price: GamePriceResp = app
+        assert_eq!(config.minted, 4);
+        let resp: ScoreboardListResp = app
             .wrap()
-            .query_wasm_smart(&addr, &QueryMsg::Price {})
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::ScoreList {
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    start_after: None,
+                    limit: Some(1),
+                },
+            )
             .unwrap();
-        assert_eq!(price, GamePriceResp { price: 123 });
+        assert_eq!(resp.scores[0].entry.user.score, Uint64::new(300));
 
-        let price: GameCounterResp = app
-            .wrap()
-            .query_wasm_smart(&addr, &QueryMsg::GameCounter {})
-            .unwrap();
-        assert_eq!(price, GameCounterResp { game_counter: 1 });
+        app.execute_contract(
+            Addr::unchecked("admin1"),
+            addr.clone(),
+            &ExecuteMsg::SetTrophyConfig { config: None },
+            &[],
+        )
+        .unwrap();
+        let resp = add_score(&mut app, "player1", 400);
+        assert!(minted(&resp).is_empty());
+    }
 
-        assert_eq!(
+    #[test]
+    fn nft_pass() {
+        let mut app = funded_app(&["player1", "player2"]);
+        let addr = instantiate_signed_arcade(&mut app);
+        let nft = instantiate_cw721(&mut app, &Addr::unchecked("owner"));
+        app.execute_contract(
+            Addr::unchecked("owner"),
+            nft.clone(),
+            &cw721_base::ExecuteMsg::<_, Empty>::Mint {
+                token_id: "1".to_string(),
+                owner: "player1".to_string(),
+                token_uri: None,
+                extension: TrophyMetadata {
+                    kind: TrophyKind::FirstEntry,
+                    game_id: DEFAULT_GAME_ID.to_string(),
+                    score: Uint64::new(1),
+                    rank: 1,
+                    season_id: 1,
+                    time: app.block_info().time,
+                },
+            },
+            &[],
+        )
+        .unwrap();
+        let execute = |app: &mut App, sender: &str, msg, funds: &[Coin]| {
+            app.execute_contract(
+                Addr::unchecked(sender),
+                addr.clone(),
+                &msg,
+                funds,
+            )
+            .map_err(|err| err.downcast::<ContractError>().unwrap())
+        };
+        let play = || ExecuteMsg::Play {
+            game_id: DEFAULT_GAME_ID.to_string(),
+            token_id: Some("1".to_string()),
+        };
+        let set_pass = |discount_bps, daily_plays| ExecuteMsg::SetNftPass {
+            game_id: DEFAULT_GAME_ID.to_string(),
+            pass: Some(NftPass {
+                contract: nft.clone(),
+                discount_bps,
+                daily_plays,
+            }),
+        };
+        let plays_left = |app: &App| {
+            let resp: NftPlaysLeftResp = app
+                .wrap()
+                .query_wasm_smart(
+                    &addr,
+                    &QueryMsg::NftPlaysLeft {
+                        game_id: DEFAULT_GAME_ID.to_string(),
+                        token_id: "1".to_string(),
+                    },
+                )
+                .unwrap();
+            resp.plays_left
+        };
+        let balance = |app: &App, player: &str| {
             app.wrap()
-                .query_balance("user1", "aconst")
+                .query_balance(player, "aconst")
                 .unwrap()
                 .amount
-                .u128(),
-            99667
+                .u128()
+        };
+
+        let err = execute(&mut app, "player1", play(), &[]).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::NoNftPass {
+                game_id: DEFAULT_GAME_ID.to_string()
+            }
+        );
+        let err =
+            execute(&mut app, "admin1", set_pass(10001, 2), &[]).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidNftDiscount {
+                discount_bps: 10001
+            }
         );
+        execute(&mut app, "admin1", set_pass(10000, 2), &[]).unwrap();
 
+        let err = execute(&mut app, "player2", play(), &[]).unwrap_err();
         assert_eq!(
-            app.wrap()
-                .query_balance(&addr, "aconst")
-                .unwrap()
-                .amount
-                .u128(),
-            111
+            err,
+            ContractError::NotTokenOwner {
+                token_id: "1".to_string()
+            }
         );
 
+        // free plays up to the daily quota of the token
+        assert_eq!(plays_left(&app), 2);
+        for _ in 0..2 {
+            execute(&mut app, "player1", play(), &[]).unwrap();
+        }
+        assert_eq!(balance(&app, "player1"), 100);
+        assert_eq!(plays_left(&app), 0);
+        let err = execute(&mut app, "player1", play(), &[]).unwrap_err();
         assert_eq!(
-            app.wrap()
-                .query_balance("admin1", "aconst")
-                .unwrap()
-                .amount
-                .u128(),
-            111
+            err,
+            ContractError::NftPlaysUsed {
+                token_id: "1".to_string(),
+                daily_plays: 2
+            }
         );
 
+        // the quota starts over the next day, at the new discount
+        app.update_block(|block| block.time = block.time.plus_seconds(86_400));
+        assert_eq!(plays_left(&app), 2);
+        execute(&mut app, "admin1", set_pass(5000, 2), &[]).unwrap();
+        execute(
+            &mut app,
+            "admin1",
+            ExecuteMsg::UpdatePrice {
+                game_id: DEFAULT_GAME_ID.to_string(),
+                price: 10,
+            },
+            &[],
+        )
+        .unwrap();
+        let err = execute(&mut app, "player1", play(), &coins(4, "aconst"))
+            .unwrap_err();
         assert_eq!(
-            app.wrap()
-                .query_balance("admin2", "aconst")
-                .unwrap()
-                .amount
-                .u128(),
-            111
+            err,
+            ContractError::InsufficientPayment {
+                required: 5,
+                sent: 4
+            }
         );
+        let resp =
+            execute(&mut app, "player1", play(), &coins(5, "aconst")).unwrap();
+        assert!(resp.events.iter().any(|event| event
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "nft_pass" && attr.value == "1")));
+        assert_eq!(balance(&app, "player1"), 95);
+        assert_eq!(plays_left(&app), 1);
+    }
 
-        let arcade_balance = app.wrap()
-            .query_balance(addr.clone(), "aconst")
+    #[test]
+    fn events() {
+        let mut app = funded_app(&["player1", "player2"]);
+        let addr = instantiate_signed_arcade(&mut app);
+        let game_id = || DEFAULT_GAME_ID.to_string();
+        let execute = |app: &mut App, sender: &str, msg: ExecuteMsg| {
+            app.execute_contract(
+                Addr::unchecked(sender),
+                addr.clone(),
+                &msg,
+                &[],
+            )
             .unwrap()
-            .amount
-            .u128();
-
-        assert_eq!(arcade_balance, 111);
+        };
+        let event = |ty: &str, attrs: &[(&str, &str)]| {
+            Event::new(format!("wasm-arcade_{ty}"))
+                .add_attributes(attrs.iter().map(|(key, value)| (*key, *value)))
+        };
 
-        let user2_balance_1 = app.wrap()
-            .query_balance("test2", "aconst")
-            .unwrap()
-            .amount
-            .u128();
+        let resp = execute(
+            &mut app,
+            "owner",
+            ExecuteMsg::AddAdmin {
+                admins: vec!["admin1".to_string(), "admin2".to_string()],
+            },
+        );
+        // admin1 was already an admin
+        assert!(resp.has_event(&event("admin_added", &[("admin", "admin2")])));
+        assert!(!resp.has_event(&event("admin_added", &[("admin", "admin1")])));
+        let resp = execute(&mut app, "admin2", ExecuteMsg::Leave {});
+        assert!(resp.has_event(&event("admin_removed", &[("admin", "admin2")])));
 
-        assert_eq!(user2_balance_1, 0);
+        let resp = execute(
+            &mut app,
+            "admin1",
+            ExecuteMsg::UpdatePrice {
+                game_id: game_id(),
+                price: 2,
+            },
+        );
+        assert!(resp.has_event(&event(
+            "price_updated",
+            &[
+                ("game_id", "default"),
+                ("asset", "aconst"),
+                ("previous_price", "1"),
+                ("price", "2"),
+            ]
+        )));
 
-        let user1 = test_user("user1", 299, "test1".to_string());
-        let _resp = app
+        let resp = app
             .execute_contract(
-                Addr::unchecked("admin1"),
+                Addr::unchecked("player1"),
                 addr.clone(),
-                &ExecuteMsg::AddTopUser { user: user1.clone() },
-                &[],
+                &ExecuteMsg::Play {
+                    game_id: game_id(),
+                    token_id: None,
+                },
+                &coins(2, "aconst"),
             )
             .unwrap();
+        assert!(resp.has_event(&event(
+            "game_played",
+            &[
+                ("game_id", "default"),
+                ("season_id", "1"),
+                ("session_id", "1"),
+                ("player", "player1"),
+                ("asset", "aconst"),
+                ("amount", "2"),
+                ("prize_pool_share", "1"),
+                ("paid_with_credit", "false"),
+                ("nft_pass", "none"),
+                ("game_counter", "1"),
+            ]
+        )));
+        app.execute_contract(
+            Addr::unchecked("player2"),
+            addr.clone(),
+            &ExecuteMsg::Play {
+                game_id: game_id(),
+                token_id: None,
+            },
+            &coins(2, "aconst"),
+        )
+        .unwrap();
 
-        let user2 = test_user("user2", 300, "test2".to_string());
-        let _resp = app
-            .execute_contract(
-                Addr::unchecked("admin1"),
-                addr.clone(),
-                &ExecuteMsg::AddTopUser { user: user2.clone() },
-                &[],
-            )
-            .unwrap();
+        let resp = execute(
+            &mut app,
+            "admin1",
+            ExecuteMsg::AddTopUser {
+                game_id: game_id(),
+                user: test_user("player1", 100, "player1".to_string()),
+                session_id: 1,
+            },
+        );
+        assert!(resp.has_event(&event(
+            "score_submitted",
+            &[
+                ("game_id", "default"),
+                ("season_id", "1"),
+                ("session_id", "1"),
+                ("player", "player1"),
+                ("name", "player1"),
+                ("score", "100"),
+                ("rank", "1"),
+            ]
+        )));
+        assert!(resp.has_event(&event(
+            "new_high_score",
+            &[
+                ("player", "player1"),
+                ("score", "100"),
+                ("previous_player", "none"),
+                ("previous_score", "none"),
+            ]
+        )));
 
-        let arcade_balance_2 = app.wrap()
-            .query_balance(addr.clone(), "aconst")
-            .unwrap()
-            .amount
-            .u128();
+        let resp = execute(
+            &mut app,
+            "admin1",
+            ExecuteMsg::AddTopUser {
+                game_id: game_id(),
+                user: test_user("player2", 200, "player2".to_string()),
+                session_id: 2,
+            },
+        );
+        assert!(resp.has_event(&event(
+            "new_high_score",
+            &[
+                ("player", "player2"),
+                ("score", "200"),
+                ("previous_player", "player1"),
+                ("previous_score", "100"),
+            ]
+        )));
 
-        let user2_balance_2 = app.wrap()
-            .query_balance("test2", "aconst")
-            .unwrap()
-            .amount
-            .u128();
+        execute(
+            &mut app,
+            "admin1",
+            ExecuteMsg::SetPayoutSchedule {
+                game_id: game_id(),
+                schedule: Some(vec![10000]),
+            },
+        );
+        let resp = execute(
+            &mut app,
+            "admin1",
+            ExecuteMsg::EndSeason { game_id: game_id() },
+        );
+        assert!(resp.has_event(&event(
+            "prize_paid",
+            &[
+                ("game_id", "default"),
+                ("season_id", "1"),
+                ("recipient", "player2"),
+                ("rank", "1"),
+                ("score", "200"),
+                ("asset", "aconst"),
+                ("amount", "2"),
+                ("held", "false"),
+            ]
+        )));
+        assert!(resp.has_event(&event(
+            "season_ended",
+            &[
+                ("game_id", "default"),
+                ("season_id", "1"),
+                ("winner", "player2"),
+                ("winning_score", "200"),
+                ("games_played", "2"),
+                ("distributed", "2"),
+                ("next_season_id", "2"),
+            ]
+        )));
 
-        // arcade balance should be empty as the whole balance should be sent to the winner user
-        assert_eq!(arcade_balance_2, 0);
-        assert_eq!(user2_balance_2, 111)
+        let resp = execute(
+            &mut app,
+            "admin1",
+            ExecuteMsg::SetPayoutSchedule {
+                game_id: game_id(),
+                schedule: None,
+            },
+        );
+        assert!(resp.has_event(&event(
+            "payout_schedule_updated",
+            &[("previous_schedule", "[10000]"), ("schedule", "null")]
+        )));
     }
 }
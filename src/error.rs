@@ -1,15 +1,109 @@
-use cosmwasm_std::{Addr, StdError};
+use cosmwasm_std::{Addr, StdError, VerificationError};
 use cw_utils::PaymentError;
 use thiserror::Error;
 
+use crate::state::{Feature, KeyAlgorithm, Role};
+
 #[derive(Error, Debug, PartialEq)]
 pub enum ContractError {
     #[error("{0}")]
     StdError(#[from] StdError),
-    #[error("{sender} is not contract admin")]
-    Unauthorized { sender: Addr },
+    #[error("{sender} is missing the {role:?} role")]
+    MissingRole { sender: Addr, role: Role },
+    #[error("the Owner role only changes by an ownership transfer")]
+    OwnerRole,
+    #[error("{sender} is not the pending owner")]
+    NotPendingOwner { sender: Addr },
+    #[error("{address} is not an admin")]
+    NotAdmin { address: Addr },
+    #[error("cannot remove the last admin")]
+    LastAdmin,
+    #[error("{feature:?} is paused: {reason}")]
+    Paused { feature: Feature, reason: String },
+    #[error("{recipient} has no held payouts")]
+    NoHeldPayouts { recipient: Addr },
+    #[error("game costs {required}, {sent} was sent")]
+    InsufficientPayment { required: u128, sent: u128 },
+    #[error("game costs exactly {required}, {sent} was sent")]
+    Overpayment { required: u128, sent: u128 },
+    #[error("game is not paid in {denom}")]
+    UnexpectedDenom { denom: String },
+    #[error("cannot buy zero credits")]
+    ZeroCredits,
+    #[error("{address} cannot hold more credits for game {game_id}")]
+    CreditsOverflow { address: Addr, game_id: String },
+    #[error("{address} has no credits for game {game_id}")]
+    NoCredits { address: Addr, game_id: String },
+    #[error("game {game_id} has unspent credits")]
+    CreditsOutstanding { game_id: String },
+    #[error("bulk discount of {discount_bps} bps is above 10000")]
+    InvalidBulkDiscount { discount_bps: u16 },
+    #[error("game {game_id} has no NFT pass")]
+    NoNftPass { game_id: String },
+    #[error("NFT pass discount of {discount_bps} bps is above 10000")]
+    InvalidNftDiscount { discount_bps: u16 },
+    #[error("sender does not own token {token_id}")]
+    NotTokenOwner { token_id: String },
+    #[error("token {token_id} has used its {daily_plays} plays of the day")]
+    NftPlaysUsed { token_id: String, daily_plays: u32 },
     #[error("Payment error: {0}")]
     Payment(#[from] PaymentError),
     #[error("arcade balance has no fundsÏ")]
     NoFunds,
+    #[error("Signature verification error: {0}")]
+    Verification(#[from] VerificationError),
+    #[error("unknown game server key: {name}")]
+    UnknownServerKey { name: String },
+    #[error("invalid {algorithm:?} public key")]
+    InvalidPublicKey { algorithm: KeyAlgorithm },
+    #[error("score attestation signature is invalid")]
+    InvalidSignature,
+    #[error("nonce {nonce} was already used")]
+    NonceUsed { nonce: u64 },
+    #[error("session {id} does not exist")]
+    SessionNotFound { id: u64 },
+    #[error("session {id} does not belong to {player}")]
+    SessionPlayerMismatch { id: u64, player: Addr },
+    #[error("session {id} is already closed")]
+    SessionClosed { id: u64 },
+    #[error("session {id} has expired")]
+    SessionExpired { id: u64 },
+    #[error("session {id} was not opened for game {game_id}")]
+    SessionGameMismatch { id: u64, game_id: String },
+    #[error("game {game_id} does not exist")]
+    GameNotFound { game_id: String },
+    #[error("game {game_id} already exists")]
+    GameExists { game_id: String },
+    #[error("game {game_id} is paused")]
+    GamePaused { game_id: String },
+    #[error("game {game_id} is retired")]
+    GameRetired { game_id: String },
+    #[error("game name must not be empty")]
+    EmptyGameName,
+    #[error("scoreboard size must not be zero")]
+    ZeroBoardSize,
+    #[error("season duration must not be zero")]
+    ZeroSeasonDuration,
+    #[error("price {price} is outside of the bounds {min} to {max}")]
+    PriceOutOfBounds { price: u128, min: u128, max: u128 },
+    #[error("price bounds minimum {min} is above the maximum {max}")]
+    InvalidPriceBounds { min: u128, max: u128 },
+    #[error("prize pool of game {game_id} must be empty to change its denom")]
+    PrizePoolNotEmpty { game_id: String },
+    #[error("game {game_id} already accepts {asset}")]
+    AssetAccepted { game_id: String, asset: String },
+    #[error("revenue split shares sum to {total} bps instead of 10000")]
+    InvalidRevenueSplit { total: u32 },
+    #[error("payout schedule shares sum to {total} bps, more than 10000")]
+    InvalidPayoutSchedule { total: u32 },
+    #[error("game {game_id} has no payout schedule")]
+    NoPayoutSchedule { game_id: String },
+    #[error("cannot migrate from contract {contract}")]
+    WrongContract { contract: String },
+    #[error("cannot migrate from version {from} down to {to}")]
+    MigrationDowngrade { from: String, to: String },
+    #[error("contract has no version and no known legacy layout")]
+    UnknownLayout,
+    #[error("migration needs the {field} setting")]
+    MissingMigrationField { field: String },
 }
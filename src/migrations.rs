@@ -0,0 +1,326 @@
+use std::cmp::Reverse;
+
+use cosmwasm_std::{Addr, DepsMut, Env, Uint64};
+use cw_storage_plus::Item;
+use serde::{Deserialize, Serialize};
+
+use crate::contract::save_new_game;
+use crate::error::ContractError;
+use crate::msg::MigrateMsg;
+use crate::state::{
+    AssetInfo, EntryMode, Game, GameStatus, Role, ScoreEntry, ScoreOrder, User,
+    DEFAULT_GAME_ID, GAME_COUNTER, PRIZE_POOL, REVENUE_SPLIT, SCORE_SEQ,
+    SESSION_COUNTER, SESSION_TTL, TOP_USERS, TOTAL_PRICE_DISTRIBUTED,
+};
+use crate::utils::{grant_roles, validate_revenue_split};
+
+// single game layout of the first release, all of it stored in items
+pub const LEGACY_ARCADE: Item<String> = Item::new("arcade");
+const LEGACY_MAX_TOP_SCORES: Item<u8> = Item::new("max_top_scores");
+const LEGACY_TOP: Item<Vec<LegacyUser>> = Item::new("top");
+const LEGACY_GAME_COUNTER: Item<u32> = Item::new("game_counter");
+const LEGACY_DENOM: Item<String> = Item::new("denom");
+const LEGACY_PRICE: Item<u128> = Item::new("price");
+const LEGACY_TOTAL: Item<u128> = Item::new("total");
+const LEGACY_ADMINS: Item<Vec<Addr>> = Item::new("admins");
+
+// scoreboard entry of the first release, scores were still u16
+#[derive(Serialize, Deserialize)]
+struct LegacyUser {
+    name: String,
+    address: Addr,
+    score: Reverse<u16>,
+}
+
+impl From<LegacyUser> for User {
+    fn from(user: LegacyUser) -> Self {
+        User {
+            name: user.name,
+            address: user.address,
+            score: Uint64::from(user.score.0),
+        }
+    }
+}
+
+// moves the single game of the first release to the default game and sets
+// up the state added since then
+pub fn migrate_single_game(
+    deps: DepsMut,
+    env: &Env,
+    msg: &MigrateMsg,
+) -> Result<(), ContractError> {
+    let missing = |field: &str| ContractError::MissingMigrationField {
+        field: field.to_string(),
+    };
+    let session_ttl = msg.session_ttl.ok_or_else(|| missing("session_ttl"))?;
+    let revenue_split = msg
+        .revenue_split
+        .clone()
+        .ok_or_else(|| missing("revenue_split"))?;
+    validate_revenue_split(deps.api, &revenue_split)?;
+
+    let game = Game {
+        name: LEGACY_ARCADE.load(deps.storage)?,
+        asset: AssetInfo::Native(LEGACY_DENOM.load(deps.storage)?),
+        price: LEGACY_PRICE.load(deps.storage)?,
+        max_top_scores: LEGACY_MAX_TOP_SCORES.load(deps.storage)?,
+        entry_mode: EntryMode::Multiple,
+        score_order: ScoreOrder::HigherIsBetter,
+        status: GameStatus::Active,
+        payout_schedule: None,
+        season_duration: None,
+    };
+    save_new_game(deps.storage, &env.block, DEFAULT_GAME_ID, &game)?;
+
+    // the old board is kept in heap order, its entries get sequence numbers
+    // in rank order so the ties stay as they were ranked
+    let mut top = LEGACY_TOP.load(deps.storage)?;
+    top.sort_by_key(|user| user.score);
+    let board: Vec<ScoreEntry> = top
+        .into_iter()
+        .zip(1..)
+        .map(|(user, seq)| ScoreEntry {
+            user: user.into(),
+            height: env.block.height,
+            time: env.block.time,
+            seq,
+        })
+        .collect();
+    SCORE_SEQ.save(deps.storage, &(board.len() as u64))?;
+    TOP_USERS.save(deps.storage, DEFAULT_GAME_ID, &board)?;
+    let counter = LEGACY_GAME_COUNTER.load(deps.storage)?;
+    GAME_COUNTER.save(deps.storage, DEFAULT_GAME_ID, &counter)?;
+    let total = LEGACY_TOTAL.load(deps.storage)?;
+    TOTAL_PRICE_DISTRIBUTED.save(deps.storage, DEFAULT_GAME_ID, &total)?;
+    // the whole balance of the first release was the prize pool
+    let balance = deps
+        .querier
+        .query_balance(&env.contract.address, game.asset.to_string())?
+        .amount
+        .u128();
+    PRIZE_POOL.save(deps.storage, DEFAULT_GAME_ID, &balance)?;
+
+    SESSION_COUNTER.save(deps.storage, &0)?;
+    SESSION_TTL.save(deps.storage, &session_ttl)?;
+    REVENUE_SPLIT.save(deps.storage, &revenue_split)?;
+
+    LEGACY_ARCADE.remove(deps.storage);
+    LEGACY_MAX_TOP_SCORES.remove(deps.storage);
+    LEGACY_TOP.remove(deps.storage);
+    LEGACY_GAME_COUNTER.remove(deps.storage);
+    LEGACY_DENOM.remove(deps.storage);
+    LEGACY_PRICE.remove(deps.storage);
+    LEGACY_TOTAL.remove(deps.storage);
+    Ok(())
+}
+
+// roles replaced the flat admin list: the admins keep every operational
+// role and the owner named in the migrate message becomes the Owner
+pub fn assign_roles(
+    deps: DepsMut,
+    msg: &MigrateMsg,
+) -> Result<(), ContractError> {
+    let owner = msg.owner.as_ref().ok_or_else(|| {
+        ContractError::MissingMigrationField {
+            field: "owner".to_string(),
+        }
+    })?;
+    let owner = deps.api.addr_validate(owner)?;
+    grant_roles(deps.storage, &owner, &[Role::Owner])?;
+    for admin in LEGACY_ADMINS.load(deps.storage)? {
+        grant_roles(deps.storage, &admin, &Role::OPERATIONAL)?;
+    }
+    LEGACY_ADMINS.remove(deps.storage);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_dependencies_with_balance, mock_env, mock_info,
+        MockApi, MockQuerier, MockStorage,
+    };
+    use cosmwasm_std::{coins, from_json, OwnedDeps, Storage};
+
+    use super::*;
+    use crate::contract::{instantiate, migrate, query};
+    use crate::msg::{
+        GameResp, InstantiateMsg, PrizePoolResp, QueryMsg, RolesResp,
+        ScoreboardListResp,
+    };
+    use crate::state::{RevenueSplit, GAMES};
+
+    fn revenue_split() -> RevenueSplit {
+        RevenueSplit {
+            prize_pool_bps: 5000,
+            treasury: None,
+            developer: None,
+            operators_bps: 5000,
+            burn_bps: 0,
+        }
+    }
+
+    fn migrate_msg() -> MigrateMsg {
+        MigrateMsg {
+            session_ttl: Some(100),
+            revenue_split: Some(revenue_split()),
+            owner: Some("owner".to_string()),
+        }
+    }
+
+    // raw state of a contract instantiated with the first release
+    fn single_game_fixture(storage: &mut dyn Storage) {
+        storage.set(b"admins", br#"["admin1"]"#);
+        storage.set(b"arcade", br#""Pac-Man""#);
+        storage.set(b"max_top_scores", b"3");
+        storage.set(
+            b"top",
+            br#"[{"name":"Bob","address":"player2","score":900},{"name":"Wotori","address":"player1","score":27000},{"name":"Eve","address":"player3","score":900}]"#,
+        );
+        storage.set(b"game_counter", b"5");
+        storage.set(b"denom", br#""aconst""#);
+        storage.set(b"price", br#""250""#);
+        storage.set(b"total", br#""1000""#);
+    }
+
+    #[test]
+    fn test_migrate_single_game() {
+        let mut deps = mock_dependencies_with_balance(&coins(625, "aconst"));
+        single_game_fixture(&mut deps.storage);
+
+        let err = migrate(
+            deps.as_mut(),
+            mock_env(),
+            MigrateMsg {
+                session_ttl: Some(100),
+                revenue_split: None,
+                owner: Some("owner".to_string()),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::MissingMigrationField {
+                field: "revenue_split".to_string()
+            }
+        );
+
+        migrate(deps.as_mut(), mock_env(), migrate_msg()).unwrap();
+
+        let query =
+            |msg: QueryMsg| query(deps.as_ref(), mock_env(), msg).unwrap();
+        let game: GameResp = from_json(query(QueryMsg::Game {
+            game_id: DEFAULT_GAME_ID.to_string(),
+        }))
+        .unwrap();
+        assert_eq!(game.game.name, "Pac-Man");
+        assert_eq!(game.game.price, 250);
+        assert_eq!(game.game.max_top_scores, 3);
+        let board: ScoreboardListResp = from_json(query(QueryMsg::ScoreList {
+            game_id: DEFAULT_GAME_ID.to_string(),
+            start_after: None,
+            limit: None,
+        }))
+        .unwrap();
+        let names: Vec<_> = board
+            .scores
+            .iter()
+            .map(|score| (score.rank, score.entry.user.name.as_str()))
+            .collect();
+        assert_eq!(names, vec![(1, "Wotori"), (2, "Bob"), (3, "Eve")]);
+        assert_eq!(board.scores[0].entry.user.score, Uint64::new(27000));
+        let pool: PrizePoolResp = from_json(query(QueryMsg::PrizePool {
+            game_id: DEFAULT_GAME_ID.to_string(),
+        }))
+        .unwrap();
+        assert_eq!(pool.prize_pool, 625);
+        assert_eq!(
+            GAME_COUNTER.load(&deps.storage, DEFAULT_GAME_ID).unwrap(),
+            5
+        );
+        assert!(!LEGACY_ARCADE.exists(&deps.storage));
+        let roles = |address: &str| -> Vec<Role> {
+            let resp: RolesResp = from_json(query(QueryMsg::Roles {
+                address: address.to_string(),
+            }))
+            .unwrap();
+            resp.roles
+        };
+        assert_eq!(roles("owner"), vec![Role::Owner]);
+        assert_eq!(roles("admin1"), Role::OPERATIONAL);
+        assert_eq!(
+            cw2::get_contract_version(&deps.storage).unwrap().version,
+            env!("CARGO_PKG_VERSION")
+        );
+    }
+
+    fn instantiated() -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies();
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            InstantiateMsg {
+                arcade: "Pac-Man".to_string(),
+                admins: vec!["admin1".to_string()],
+                max_top_score: 3,
+                denom: "aconst".to_string(),
+                price_peer_game: 1,
+                session_ttl: 100,
+                revenue_split: revenue_split(),
+                entry_mode: EntryMode::Multiple,
+                score_order: ScoreOrder::HigherIsBetter,
+            },
+        )
+        .unwrap();
+        deps
+    }
+
+    #[test]
+    fn test_migrate_unknown_layout() {
+        let mut deps = mock_dependencies();
+        let err =
+            migrate(deps.as_mut(), mock_env(), migrate_msg()).unwrap_err();
+        assert_eq!(err, ContractError::UnknownLayout);
+    }
+
+    #[test]
+    fn test_migrate_versions() {
+        // the current layout migrates in place
+        let mut deps = instantiated();
+        let resp = migrate(deps.as_mut(), mock_env(), migrate_msg()).unwrap();
+        assert_eq!(resp.attributes[1].value, env!("CARGO_PKG_VERSION"));
+        assert!(GAMES.has(&deps.storage, DEFAULT_GAME_ID));
+
+        cw2::set_contract_version(
+            &mut deps.storage,
+            "crates.io:cw-arcade",
+            "99.0.0",
+        )
+        .unwrap();
+        let err =
+            migrate(deps.as_mut(), mock_env(), migrate_msg()).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::MigrationDowngrade {
+                from: "99.0.0".to_string(),
+                to: env!("CARGO_PKG_VERSION").to_string(),
+            }
+        );
+
+        cw2::set_contract_version(
+            &mut deps.storage,
+            "crates.io:cw20-base",
+            "1.0.0",
+        )
+        .unwrap();
+        let err =
+            migrate(deps.as_mut(), mock_env(), migrate_msg()).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::WrongContract {
+                contract: "crates.io:cw20-base".to_string(),
+            }
+        );
+    }
+}
@@ -1,8 +1,14 @@
-use cosmwasm_std::Addr;
+use cosmwasm_std::{Addr, Binary, Uint64};
+use cw20::Cw20ReceiveMsg;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::state::User;
+use crate::state::{
+    Asset, AssetInfo, BulkDiscount, Credits, EntryMode, Feature, Game,
+    KeyAlgorithm, NftPass, PauseInfo, PaymentMode, PlayerStats, PriceBounds,
+    RevenueSplit, Role, ScoreEntry, ScoreOrder, Season, SeasonDuration,
+    SeasonRecord, Session, TrophyConfig, TrophyMetadata, User,
+};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
@@ -11,25 +17,335 @@ pub struct InstantiateMsg {
     pub max_top_score: u8,
     pub denom: String,
     pub price_peer_game: u128,
+    pub session_ttl: u64,
+    pub revenue_split: RevenueSplit,
+    pub entry_mode: EntryMode,
+    pub score_order: ScoreOrder,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {
+    // settings that did not exist in the single game layout, required when
+    // migrating a contract stored with it
+    pub session_ttl: Option<u64>,
+    pub revenue_split: Option<RevenueSplit>,
+    // receives the Owner role when migrating a contract from before roles
+    pub owner: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub enum ExecuteMsg {
-    AddAdmin { admins: Vec<String> },
-    AddTopUser { user: User },
+    AddAdmin {
+        admins: Vec<String>,
+    },
+    AddTopUser {
+        game_id: String,
+        user: User,
+        session_id: u64,
+    },
     Leave {},
-    Play {},
-    UpdatePrice { price: u128 },
+    RemoveAdmin {
+        admin: String,
+    },
+    // `None` cancels a pending proposal
+    ProposeOwner {
+        owner: Option<String>,
+    },
+    AcceptOwnership {},
+    Pause {
+        features: Vec<Feature>,
+        reason: String,
+    },
+    Unpause {
+        features: Vec<Feature>,
+    },
+    // sends the prizes held for the sender while payouts were paused
+    ClaimHeldPayouts {},
+    // plays at the discount of the game NFT pass with `token_id`, owned by
+    // the sender
+    Play {
+        game_id: String,
+        token_id: Option<String>,
+    },
+    // CW20 tokens sent to the arcade, `msg` holds a `ReceiveMsg`. The CW20
+    // contract sends it in snake case
+    #[serde(rename = "receive")]
+    Receive(Cw20ReceiveMsg),
+    // pays for `count` games up front, played later by `Play` without funds
+    BuyCredits {
+        game_id: String,
+        count: u32,
+    },
+    // sends back what the address paid for its unspent credits
+    RefundCredits {
+        game_id: String,
+        address: String,
+    },
+    // prices the game in another asset, `None` stops accepting it
+    SetAssetPrice {
+        game_id: String,
+        asset: AssetInfo,
+        price: Option<u128>,
+    },
+    // `None` removes the pass
+    SetNftPass {
+        game_id: String,
+        pass: Option<NftPass>,
+    },
+    // an empty list removes the discounts
+    SetBulkDiscounts {
+        game_id: String,
+        discounts: Vec<BulkDiscount>,
+    },
+    UpdatePrice {
+        game_id: String,
+        price: u128,
+    },
+    // changes only the given settings of a game
+    UpdateConfig {
+        game_id: String,
+        name: Option<String>,
+        asset: Option<AssetInfo>,
+        price: Option<u128>,
+        max_top_score: Option<u8>,
+    },
+    // `None` lifts the bounds
+    SetPriceBounds {
+        bounds: Option<PriceBounds>,
+    },
+    SetPaymentMode {
+        mode: PaymentMode,
+    },
+    UpdateSessionTtl {
+        blocks: u64,
+    },
+    RegisterServerKey {
+        name: String,
+        algorithm: KeyAlgorithm,
+        public_key: Binary,
+    },
+    RemoveServerKey {
+        name: String,
+    },
+    SubmitScore {
+        game_id: String,
+        name: String,
+        score: Uint64,
+        nonce: u64,
+        server: String,
+        signature: Binary,
+        session_id: u64,
+    },
+    AddGame {
+        game_id: String,
+        name: String,
+        asset: AssetInfo,
+        price: u128,
+        max_top_score: u8,
+        entry_mode: EntryMode,
+        score_order: ScoreOrder,
+    },
+    PauseGame {
+        game_id: String,
+    },
+    ResumeGame {
+        game_id: String,
+    },
+    RetireGame {
+        game_id: String,
+    },
+    FundPrizePool {
+        game_id: String,
+    },
+    SweepSurplus {
+        denom: String,
+        recipient: String,
+    },
+    UpdateRevenueSplit {
+        revenue_split: RevenueSplit,
+    },
+    SetPayoutSchedule {
+        game_id: String,
+        schedule: Option<Vec<u16>>,
+    },
+    Distribute {
+        game_id: String,
+    },
+    SetSeasonDuration {
+        game_id: String,
+        duration: Option<SeasonDuration>,
+    },
+    EndSeason {
+        game_id: String,
+    },
+    // `None` stops minting trophies
+    SetTrophyConfig {
+        config: Option<TrophyConfig>,
+    },
+    GrantRole {
+        address: String,
+        role: Role,
+    },
+    RevokeRole {
+        address: String,
+        role: Role,
+    },
+}
+
+// hooks of CW20 tokens sent to the arcade, paid in the tokens sent
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub enum ReceiveMsg {
+    Play {
+        game_id: String,
+        token_id: Option<String>,
+    },
+    BuyCredits {
+        game_id: String,
+        count: u32,
+    },
+    FundPrizePool {
+        game_id: String,
+    },
+}
+
+// mint message of cw721-base, with the trophy metadata as its extension
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw721ExecuteMsg {
+    Mint {
+        token_id: String,
+        owner: String,
+        token_uri: Option<String>,
+        extension: TrophyMetadata,
+    },
+}
+
+// owner query of cw721-base, used to check NFT passes
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw721QueryMsg {
+    OwnerOf {
+        token_id: String,
+        include_expired: Option<bool>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OwnerOfResponse {
+    pub owner: String,
+}
+
+// payload signed by a game server to attest the score of a player, the
+// signature covers the sha256 digest of its JSON encoding
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ScoreAttestation {
+    pub contract: String,
+    pub game_id: String,
+    pub player: String,
+    pub score: Uint64,
+    pub nonce: u64,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
 pub enum QueryMsg {
     AdminsList {},
-    ScoreList {},
-    GameCounter {},
-    Price {},
-    PrizePool {},
-    TotalDistributed {},
+    ScoreList {
+        game_id: String,
+        // rank to start after, ranks start from 1
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    },
+    PlayerRank {
+        game_id: String,
+        address: String,
+    },
+    GameCounter {
+        game_id: String,
+    },
+    Price {
+        game_id: String,
+    },
+    // every accepted asset with its price, the game asset first
+    Prices {
+        game_id: String,
+    },
+    PriceBounds {},
+    PaymentMode {},
+    Credits {
+        game_id: String,
+        address: String,
+    },
+    BulkDiscounts {
+        game_id: String,
+    },
+    NftPass {
+        game_id: String,
+    },
+    // discounted plays a token has left today
+    NftPlaysLeft {
+        game_id: String,
+        token_id: String,
+    },
+    PrizePool {
+        game_id: String,
+    },
+    TotalDistributed {
+        game_id: String,
+    },
+    ServerKeys {},
+    Session {
+        id: u64,
+    },
+    PlayerSessions {
+        address: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    Game {
+        game_id: String,
+    },
+    Games {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    Surplus {
+        denom: String,
+    },
+    RevenueSplit {},
+    PayoutSchedule {
+        game_id: String,
+    },
+    PlayerStats {
+        game_id: String,
+        address: String,
+    },
+    AllPlayers {
+        game_id: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    CurrentSeason {
+        game_id: String,
+    },
+    Seasons {
+        game_id: String,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    },
+    Roles {
+        address: String,
+    },
+    Ownership {},
+    PauseState {},
+    HeldPayouts {
+        address: String,
+    },
+    TrophyConfig {},
+    RoleMembers {
+        role: Role,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
@@ -44,7 +360,19 @@ pub struct AdminsListResp {
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
 pub struct ScoreboardListResp {
-    pub scores: Vec<User>,
+    pub scores: Vec<RankedUser>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct RankedUser {
+    pub rank: u32,
+    pub entry: ScoreEntry,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct PlayerRankResp {
+    // best rank of the player, `None` when they are off the board
+    pub rank: Option<u32>,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
@@ -57,13 +385,157 @@ pub struct GamePriceResp {
     pub price: u128,
 }
 
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct PricesResp {
+    pub prices: Vec<Asset>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct PaymentModeResp {
+    pub mode: PaymentMode,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct PriceBoundsResp {
+    pub bounds: Option<PriceBounds>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct CreditsResp {
+    pub credits: Credits,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct BulkDiscountsResp {
+    pub discounts: Vec<BulkDiscount>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct NftPassResp {
+    pub pass: Option<NftPass>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct NftPlaysLeftResp {
+    pub plays_left: u32,
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
 pub struct PrizePoolResp {
+    // in the game asset
     pub prize_pool: u128,
+    // every pool, the one in the game asset first
+    pub pools: Vec<Asset>,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
 pub struct TotalDistributionResp {
     // returns total amouns of token that was distributed across gamers
     pub total_distributed: u128,
+    // totals of every asset, the game asset first
+    pub per_asset: Vec<Asset>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct ServerKeyResp {
+    pub name: String,
+    pub algorithm: KeyAlgorithm,
+    pub public_key: Binary,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct ServerKeysResp {
+    pub keys: Vec<ServerKeyResp>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct SessionResp {
+    pub session: Session,
+    pub expired: bool,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct SessionsResp {
+    pub sessions: Vec<Session>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct GameResp {
+    pub game_id: String,
+    pub game: Game,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct GamesResp {
+    pub games: Vec<GameResp>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct SurplusResp {
+    // contract balance in denom that is not part of any prize pool
+    pub surplus: u128,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct RevenueSplitResp {
+    pub revenue_split: RevenueSplit,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct PayoutScheduleResp {
+    // share of the prize pool in bps for each rank, starting from #1
+    pub schedule: Option<Vec<u16>>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct CurrentSeasonResp {
+    pub season: Season,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct SeasonsResp {
+    pub seasons: Vec<SeasonRecord>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct PlayerStatsResp {
+    pub address: Addr,
+    pub stats: PlayerStats,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct AllPlayersResp {
+    pub players: Vec<PlayerStatsResp>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct RolesResp {
+    pub roles: Vec<Role>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct RoleMembersResp {
+    pub members: Vec<Addr>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct OwnershipResp {
+    pub owner: Addr,
+    pub pending_owner: Option<Addr>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct PauseStateResp {
+    pub paused: Vec<PauseInfo>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct HeldPayoutsResp {
+    pub payouts: Vec<Asset>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct TrophyConfigResp {
+    pub config: Option<TrophyConfig>,
+    pub minted: u64,
 }
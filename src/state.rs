@@ -1,35 +1,455 @@
-use std::cmp::Reverse;
+use std::fmt;
 
-use cosmwasm_std::Addr;
-use cw_storage_plus::Item;
+use cosmwasm_std::{Addr, Binary, Empty, Timestamp, Uint128, Uint64};
+use cw_storage_plus::{Item, Map};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-pub const ADMINS: Item<Vec<Addr>> = Item::new("admins");
-pub const ARCADE: Item<String> = Item::new("arcade");
-pub const MAX_TOP_SCORES: Item<u8> = Item::new("max_top_scores");
+#[derive(
+    Serialize, Deserialize, Debug, Eq, PartialEq, Clone, Copy, JsonSchema,
+)]
+pub enum Role {
+    // grants and revokes roles and admins, held by a single address and
+    // moved only by an ownership transfer
+    Owner,
+    // game settings, prices, server keys and seasons
+    ConfigManager,
+    // adds scores to the scoreboards
+    ScoreReporter,
+    // revenue split, payouts and surplus
+    Treasurer,
+    // pauses and resumes games
+    Pauser,
+}
+
+impl Role {
+    pub const ALL: [Role; 5] = [
+        Role::Owner,
+        Role::ConfigManager,
+        Role::ScoreReporter,
+        Role::Treasurer,
+        Role::Pauser,
+    ];
+
+    // roles every admin holds
+    pub const OPERATIONAL: [Role; 4] = [
+        Role::ConfigManager,
+        Role::ScoreReporter,
+        Role::Treasurer,
+        Role::Pauser,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Owner => "owner",
+            Role::ConfigManager => "config_manager",
+            Role::ScoreReporter => "score_reporter",
+            Role::Treasurer => "treasurer",
+            Role::Pauser => "pauser",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Role> {
+        Role::ALL.into_iter().find(|role| role.as_str() == key)
+    }
+}
+
+// (role name, holder) for every granted role, role first so the holders of
+// a role are a prefix
+pub const ROLES: Map<(&str, &Addr), Empty> = Map::new("roles");
+
+// proposed owner, who becomes the owner when they accept
+pub const PENDING_OWNER: Item<Addr> = Item::new("pending_owner");
+
+// parts of the contract that can be paused independently
+#[derive(
+    Serialize, Deserialize, Debug, Eq, PartialEq, Clone, Copy, JsonSchema,
+)]
+pub enum Feature {
+    Play,
+    // `AddTopUser` and `SubmitScore`
+    ScoreSubmission,
+    // prizes won while paused are held until claimed
+    Payouts,
+    // admins, roles and ownership
+    AdminChanges,
+}
+
+impl Feature {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Feature::Play => "play",
+            Feature::ScoreSubmission => "score_submission",
+            Feature::Payouts => "payouts",
+            Feature::AdminChanges => "admin_changes",
+        }
+    }
+}
 
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, JsonSchema)]
-pub struct User {
-    pub name: String,
+pub struct PauseInfo {
+    pub feature: Feature,
+    pub reason: String,
+    pub since: Timestamp,
+}
+
+pub const PAUSED: Map<&str, PauseInfo> = Map::new("paused");
+
+// prizes won while payouts were paused, by (recipient, asset key)
+pub const HELD_PAYOUTS: Map<(&Addr, &str), u128> = Map::new("held_payouts");
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, JsonSchema)]
+pub struct Share {
     pub address: Addr,
-    pub score: Reverse<u16>, // Reverse is used for creating a min-heap instead of a max-heap
+    pub bps: u16,
+}
+
+// how `Play` payments are divided, all shares are in basis points and sum
+// to 10000; operators share is divided evenly between the admins
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, JsonSchema)]
+pub struct RevenueSplit {
+    pub prize_pool_bps: u16,
+    pub treasury: Option<Share>,
+    pub developer: Option<Share>,
+    pub operators_bps: u16,
+    pub burn_bps: u16,
+}
+
+pub const REVENUE_SPLIT: Item<RevenueSplit> = Item::new("revenue_split");
+
+// game created at instantiation from the single-game instantiate fields
+pub const DEFAULT_GAME_ID: &str = "default";
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, JsonSchema)]
+pub enum GameStatus {
+    Active,
+    Paused,
+    Retired,
 }
 
-impl Ord for User {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.score.cmp(&other.score)
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, JsonSchema)]
+pub enum EntryMode {
+    // a player can hold any number of scoreboard entries
+    Multiple,
+    // a player holds a single entry, replaced only by a better score
+    OnePerAddress,
+}
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, JsonSchema)]
+pub enum ScoreOrder {
+    HigherIsBetter,
+    // time trials, racing and golf style games
+    LowerIsBetter,
+}
+
+// token a game is paid with and pays its prizes in
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, JsonSchema)]
+pub enum AssetInfo {
+    Native(String),
+    // address of the CW20 token contract
+    Cw20(Addr),
+}
+
+impl AssetInfo {
+    // key of the asset in maps of several assets, CW20 tokens are prefixed
+    // so they never clash with a native denom
+    pub fn key(&self) -> String {
+        match self {
+            AssetInfo::Native(denom) => denom.clone(),
+            AssetInfo::Cw20(token) => format!("cw20:{token}"),
+        }
+    }
+
+    pub fn from_key(key: &str) -> AssetInfo {
+        match key.strip_prefix("cw20:") {
+            Some(token) => AssetInfo::Cw20(Addr::unchecked(token)),
+            None => AssetInfo::Native(key.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for AssetInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AssetInfo::Native(denom) => write!(f, "{denom}"),
+            AssetInfo::Cw20(token) => write!(f, "{token}"),
+        }
     }
 }
 
-impl PartialOrd for User {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, JsonSchema)]
+pub struct Asset {
+    pub info: AssetInfo,
+    pub amount: Uint128,
+}
+
+impl Asset {
+    pub fn new(info: AssetInfo, amount: u128) -> Self {
+        Asset {
+            info,
+            amount: Uint128::new(amount),
+        }
     }
 }
 
-pub const TOP_USERS: Item<Vec<User>> = Item::new("top");
-pub const GAME_COUNTER: Item<u32> = Item::new("game_counter");
-pub const ARCADE_DENOM: Item<String> = Item::new("denom");
-pub const PRICE_PEER_GAME: Item<u128> = Item::new("price");
-pub const TOTAL_PRICE_DISTRIBUTED: Item<u128> = Item::new("total");
+impl fmt::Display for Asset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.amount, self.info)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, JsonSchema)]
+pub struct Game {
+    pub name: String,
+    pub asset: AssetInfo,
+    pub price: u128,
+    pub max_top_scores: u8,
+    pub entry_mode: EntryMode,
+    pub score_order: ScoreOrder,
+    pub status: GameStatus,
+    // share of the prize pool in bps for each rank, starting from #1.
+    // When set, the pool is paid out by `Distribute` instead of to every
+    // new #1
+    pub payout_schedule: Option<Vec<u16>>,
+    // seasons end automatically after this long, otherwise only on
+    // `EndSeason`
+    pub season_duration: Option<SeasonDuration>,
+}
+
+pub const GAMES: Map<&str, Game> = Map::new("games");
+
+#[derive(
+    Serialize, Deserialize, Debug, Default, Eq, PartialEq, Clone, JsonSchema,
+)]
+pub enum PaymentMode {
+    // `Play` has to send exactly the game price
+    Exact,
+    // anything sent above the game price, in any denom, is sent back
+    #[default]
+    RefundChange,
+}
+
+pub const PAYMENT_MODE: Item<PaymentMode> = Item::new("payment_mode");
+
+// games bought ahead with `BuyCredits`, the amount paid for them stays in
+// escrow until a credit is played or refunded
+#[derive(
+    Serialize, Deserialize, Debug, Default, Eq, PartialEq, Clone, JsonSchema,
+)]
+pub struct Credits {
+    pub count: u32,
+    pub paid: u128,
+}
+
+// (game id, player) credit balances
+pub const CREDITS: Map<(&str, &Addr), Credits> = Map::new("credits");
+// total paid for unspent credits of a game, in the game asset
+pub const CREDIT_ESCROW: Map<&str, u128> = Map::new("credit_escrow");
+
+// price cut for buying at least `min_count` credits at once
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, JsonSchema)]
+pub struct BulkDiscount {
+    pub min_count: u32,
+    pub discount_bps: u16,
+}
+
+pub const BULK_DISCOUNTS: Map<&str, Vec<BulkDiscount>> =
+    Map::new("bulk_discounts");
+
+// partner CW721 collection whose holders play a game at a discount, each
+// token for a limited number of plays a day
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, JsonSchema)]
+pub struct NftPass {
+    pub contract: Addr,
+    // 10000 makes the plays free
+    pub discount_bps: u16,
+    pub daily_plays: u32,
+}
+
+pub const NFT_PASSES: Map<&str, NftPass> = Map::new("nft_passes");
+
+// plays of a token on `day`, counted in days since the epoch
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, JsonSchema)]
+pub struct NftPassUses {
+    pub day: u64,
+    pub count: u32,
+}
+
+// (game id, token id) uses of every token that played a game
+pub const NFT_PASS_USES: Map<(&str, &str), NftPassUses> =
+    Map::new("nft_pass_uses");
+
+// range every game price has to be in, set by the owner
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, JsonSchema)]
+pub struct PriceBounds {
+    pub min: u128,
+    pub max: u128,
+}
+
+pub const PRICE_BOUNDS: Item<PriceBounds> = Item::new("price_bounds");
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, JsonSchema)]
+pub struct User {
+    pub name: String,
+    pub address: Addr,
+    pub score: Uint64,
+}
+
+// scoreboard entry, stamped with the block and a sequence number when the
+// score was accepted
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, JsonSchema)]
+pub struct ScoreEntry {
+    pub user: User,
+    pub height: u64,
+    pub time: Timestamp,
+    pub seq: u64,
+}
+
+pub const SCORE_SEQ: Item<u64> = Item::new("score_seq");
+
+// per game state, keyed by game id
+pub const TOP_USERS: Map<&str, Vec<ScoreEntry>> = Map::new("top");
+pub const GAME_COUNTER: Map<&str, u32> = Map::new("game_counter");
+pub const PRIZE_POOL: Map<&str, u128> = Map::new("prize_pool");
+pub const TOTAL_PRICE_DISTRIBUTED: Map<&str, u128> = Map::new("total");
+
+// assets a game accepts besides its own, with their price. Each of them has
+// its own prize pool and distributed total, keyed by game id and asset key
+pub const ACCEPTED_ASSETS: Map<(&str, &str), u128> =
+    Map::new("accepted_assets");
+pub const ASSET_POOLS: Map<(&str, &str), u128> = Map::new("asset_pools");
+pub const ASSET_DISTRIBUTED: Map<(&str, &str), u128> =
+    Map::new("asset_distributed");
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, JsonSchema)]
+pub enum SeasonDuration {
+    Seconds(u64),
+    Blocks(u64),
+}
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, JsonSchema)]
+pub struct Season {
+    pub id: u32,
+    pub start_height: u64,
+    pub start_time: Timestamp,
+    // game counter and distributed total when the season started, both of
+    // them keep counting across seasons
+    pub games_at_start: u32,
+    pub distributed_at_start: u128,
+}
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, JsonSchema)]
+pub struct SeasonRecord {
+    pub id: u32,
+    pub start_height: u64,
+    pub start_time: Timestamp,
+    pub end_height: u64,
+    pub end_time: Timestamp,
+    pub winner: Option<ScoreEntry>,
+    // final scoreboard, best score first
+    pub scoreboard: Vec<ScoreEntry>,
+    pub games_played: u32,
+    pub distributed: u128,
+}
+
+#[derive(
+    Serialize, Deserialize, Debug, Default, Eq, PartialEq, Clone, JsonSchema,
+)]
+pub struct PlayerStats {
+    pub personal_best: Option<Uint64>,
+    pub games_played: u32,
+    pub total_spent: u128,
+    pub total_winnings: u128,
+    pub last_played: Option<Timestamp>,
+}
+
+// stats of everyone who played a game, keyed by game id and player
+pub const PLAYER_STATS: Map<(&str, &Addr), PlayerStats> =
+    Map::new("player_stats");
+
+pub const CURRENT_SEASON: Map<&str, Season> = Map::new("current_season");
+// archived seasons, keyed by game id and season id
+pub const SEASONS: Map<(&str, u32), SeasonRecord> = Map::new("seasons");
+
+// cw721-base contract trophies are minted with, the arcade has to be its
+// minter
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, JsonSchema)]
+pub struct TrophyConfig {
+    pub contract: Addr,
+    // number of places on the final scoreboard of a season earning a trophy
+    pub season_places: u8,
+}
+
+pub const TROPHY_CONFIG: Item<TrophyConfig> = Item::new("trophy_config");
+// last trophy number handed out, failed mints included
+pub const TROPHY_COUNTER: Item<u64> = Item::new("trophy_counter");
+// trophies the trophy contract confirmed
+pub const TROPHIES_MINTED: Item<u64> = Item::new("trophies_minted");
+// mints waiting for their reply, keyed by trophy number
+pub const PENDING_TROPHIES: Map<u64, PendingTrophy> =
+    Map::new("pending_trophies");
+// (game id, player) of everyone who ever got on the scoreboard of a game
+pub const BOARD_ENTRANTS: Map<(&str, &Addr), Empty> =
+    Map::new("board_entrants");
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, JsonSchema)]
+pub enum TrophyKind {
+    // first time on the scoreboard of a game
+    FirstEntry,
+    // taking #1 from another player
+    TopRank,
+    // placing on the final scoreboard of a season
+    SeasonPlacement,
+}
+
+// extension of a minted trophy, `rank` starts from 1
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, JsonSchema)]
+pub struct TrophyMetadata {
+    pub kind: TrophyKind,
+    pub game_id: String,
+    pub score: Uint64,
+    pub rank: u32,
+    pub season_id: u32,
+    pub time: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, JsonSchema)]
+pub struct PendingTrophy {
+    pub owner: Addr,
+    pub metadata: TrophyMetadata,
+}
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, JsonSchema)]
+pub enum KeyAlgorithm {
+    Secp256k1,
+    Ed25519,
+}
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, JsonSchema)]
+pub struct ServerKey {
+    pub algorithm: KeyAlgorithm,
+    pub public_key: Binary,
+}
+
+// game-server keys allowed to sign score attestations, keyed by server name
+pub const SERVER_KEYS: Map<&str, ServerKey> = Map::new("server_keys");
+// (player, nonce) pairs of attestations that were already accepted
+pub const USED_NONCES: Map<(&Addr, u64), Empty> = Map::new("used_nonces");
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, JsonSchema)]
+pub struct Session {
+    pub id: u64,
+    pub game_id: String,
+    pub player: Addr,
+    pub height: u64,
+    pub price_paid: u128,
+    pub closed: bool,
+}
+
+// sessions opened by `Play`, each one can back a single score submission
+pub const SESSIONS: Map<u64, Session> = Map::new("sessions");
+pub const PLAYER_SESSIONS: Map<(&Addr, u64), Empty> =
+    Map::new("player_sessions");
+pub const SESSION_COUNTER: Item<u64> = Item::new("session_counter");
+// number of blocks after which an open session can no longer be used
+pub const SESSION_TTL: Item<u64> = Item::new("session_ttl");
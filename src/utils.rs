@@ -1,96 +1,1344 @@
-use std::collections::BinaryHeap;
+use std::cmp::Ordering;
 
-use cosmwasm_std::{BalanceResponse, BankMsg, BankQuery, DepsMut, Env, Response, StdError};
 use crate::error::ContractError;
-use cosmwasm_std::{coins};
-use crate::state::{User, ARCADE_DENOM, TOTAL_PRICE_DISTRIBUTED};
+use crate::msg::{
+    Cw721ExecuteMsg, Cw721QueryMsg, OwnerOfResponse, ScoreAttestation,
+};
+use crate::state::{
+    Asset, AssetInfo, BulkDiscount, Feature, Game, KeyAlgorithm, NftPass,
+    NftPassUses, PaymentMode, PendingTrophy, PlayerStats, RevenueSplit, Role,
+    ScoreEntry, ScoreOrder, Season, SeasonDuration, SeasonRecord, ServerKey,
+    Session, TrophyKind, TrophyMetadata, User, ACCEPTED_ASSETS,
+    ASSET_DISTRIBUTED, ASSET_POOLS, CREDITS, CREDIT_ESCROW, CURRENT_SEASON,
+    GAMES, GAME_COUNTER, HELD_PAYOUTS, NFT_PASSES, NFT_PASS_USES, PAUSED,
+    PENDING_TROPHIES, PLAYER_STATS, PRICE_BOUNDS, PRIZE_POOL, ROLES, SEASONS,
+    TOP_USERS, TOTAL_PRICE_DISTRIBUTED, TROPHY_CONFIG, TROPHY_COUNTER,
+};
+use cosmwasm_std::{
+    attr, coins, Attribute, Coin, CosmosMsg, Event, MessageInfo, SubMsg,
+    WasmMsg,
+};
+use cosmwasm_std::{
+    to_json_binary, to_json_vec, Addr, Api, BankMsg, BlockInfo, Deps, DepsMut,
+    Empty, Env, Order, Response, StdError, StdResult, Storage, Timestamp,
+    Uint128,
+};
+use cw20::Cw20ExecuteMsg;
+use cw_utils::PaymentError;
+use sha2::{Digest, Sha256};
 
-pub fn user_is_top(heap: &BinaryHeap<User>, user: &User) -> bool {
-    if let Some(highest_score_user) =
-        heap.iter().min_by(|a, b| a.score.cmp(&b.score))
-    {
-        if highest_score_user > user {
-            // > as score is Reverse<u16>
-            return true;
+// rank order of two entries, `Less` when `a` ranks above `b`. The score
+// decides first and the earlier submission wins ties
+pub fn compare_entries(
+    order: &ScoreOrder,
+    a: &ScoreEntry,
+    b: &ScoreEntry,
+) -> Ordering {
+    let by_score = match order {
+        ScoreOrder::HigherIsBetter => b.user.score.cmp(&a.user.score),
+        ScoreOrder::LowerIsBetter => a.user.score.cmp(&b.user.score),
+    };
+    by_score.then(a.seq.cmp(&b.seq))
+}
+
+pub fn ranks_above(order: &ScoreOrder, a: &ScoreEntry, b: &ScoreEntry) -> bool {
+    compare_entries(order, a, b) == Ordering::Less
+}
+
+pub fn user_is_top(
+    order: &ScoreOrder,
+    scoreboard: &[ScoreEntry],
+    entry: &ScoreEntry,
+) -> bool {
+    // an equal score does not take the top from the earlier submission
+    !scoreboard.is_empty()
+        && scoreboard.iter().all(|top| ranks_above(order, entry, top))
+}
+
+// scoreboard of a game in rank order, best score first
+pub fn ranked_scoreboard(
+    storage: &dyn Storage,
+    game_id: &str,
+) -> StdResult<Vec<ScoreEntry>> {
+    let order = GAMES.load(storage, game_id)?.score_order;
+    let mut scoreboard = TOP_USERS.load(storage, game_id)?;
+    scoreboard.sort_by(|a, b| compare_entries(&order, a, b));
+    Ok(scoreboard)
+}
+
+pub const TOTAL_BPS: u16 = 10_000;
+
+// amounts a single payment is divided into, `operators` is the amount for
+// each operator and `dust` is the rounding remainder that goes to the prize
+// pool together with `prize_pool`
+#[derive(Debug, Default, PartialEq)]
+pub struct RevenueShares {
+    pub prize_pool: u128,
+    pub treasury: u128,
+    pub developer: u128,
+    pub operators: u128,
+    pub burn: u128,
+    pub dust: u128,
+}
+
+pub fn validate_revenue_split(
+    api: &dyn Api,
+    split: &RevenueSplit,
+) -> Result<(), ContractError> {
+    for share in [&split.treasury, &split.developer].into_iter().flatten() {
+        api.addr_validate(share.address.as_str())?;
+    }
+    let total = [
+        split.prize_pool_bps,
+        split.treasury.as_ref().map_or(0, |share| share.bps),
+        split.developer.as_ref().map_or(0, |share| share.bps),
+        split.operators_bps,
+        split.burn_bps,
+    ]
+    .iter()
+    .map(|bps| u32::from(*bps))
+    .sum();
+    if total != u32::from(TOTAL_BPS) {
+        return Err(ContractError::InvalidRevenueSplit { total });
+    }
+    Ok(())
+}
+
+pub fn split_revenue(
+    split: &RevenueSplit,
+    amount: u128,
+    operators: usize,
+) -> RevenueShares {
+    let share =
+        |bps: u16| Uint128::new(amount).multiply_ratio(bps, TOTAL_BPS).u128();
+    let operators_total = share(split.operators_bps);
+    let mut shares = RevenueShares {
+        prize_pool: share(split.prize_pool_bps),
+        treasury: split.treasury.as_ref().map_or(0, |s| share(s.bps)),
+        developer: split.developer.as_ref().map_or(0, |s| share(s.bps)),
+        operators: operators_total
+            .checked_div(operators as u128)
+            .unwrap_or_default(),
+        burn: share(split.burn_bps),
+        dust: 0,
+    };
+    shares.dust = amount
+        - shares.prize_pool
+        - shares.treasury
+        - shares.developer
+        - shares.operators * operators as u128
+        - shares.burn;
+    shares
+}
+
+// a ttl too large to add to the opening height never expires
+pub fn session_expired(session: &Session, ttl: u64, env: &Env) -> bool {
+    env.block.height > session.height.saturating_add(ttl)
+}
+
+pub fn validate_public_key(key: &ServerKey) -> Result<(), ContractError> {
+    let len = key.public_key.len();
+    let valid = match key.algorithm {
+        KeyAlgorithm::Secp256k1 => len == 33 || len == 65,
+        KeyAlgorithm::Ed25519 => len == 32,
+    };
+    if !valid {
+        return Err(ContractError::InvalidPublicKey {
+            algorithm: key.algorithm.clone(),
+        });
+    }
+    Ok(())
+}
+
+pub fn attestation_digest(
+    attestation: &ScoreAttestation,
+) -> Result<Vec<u8>, StdError> {
+    let payload = to_json_vec(attestation)?;
+    Ok(Sha256::digest(payload).to_vec())
+}
+
+pub fn verify_attestation(
+    api: &dyn Api,
+    key: &ServerKey,
+    attestation: &ScoreAttestation,
+    signature: &[u8],
+) -> Result<(), ContractError> {
+    let digest = attestation_digest(attestation)?;
+    let valid = match key.algorithm {
+        KeyAlgorithm::Secp256k1 => {
+            api.secp256k1_verify(&digest, signature, &key.public_key)?
+        }
+        KeyAlgorithm::Ed25519 => {
+            api.ed25519_verify(&digest, signature, &key.public_key)?
+        }
+    };
+    if !valid {
+        return Err(ContractError::InvalidSignature);
+    }
+    Ok(())
+}
+
+// settings every game has to keep, checked whenever they are set
+pub fn validate_game(
+    storage: &dyn Storage,
+    game: &Game,
+) -> Result<(), ContractError> {
+    if game.name.trim().is_empty() {
+        return Err(ContractError::EmptyGameName);
+    }
+    if game.max_top_scores == 0 {
+        return Err(ContractError::ZeroBoardSize);
+    }
+    if let Some(bounds) = PRICE_BOUNDS.may_load(storage)? {
+        if game.price < bounds.min || game.price > bounds.max {
+            return Err(ContractError::PriceOutOfBounds {
+                price: game.price,
+                min: bounds.min,
+                max: bounds.max,
+            });
+        }
+    }
+    Ok(())
+}
+
+pub fn has_role(storage: &dyn Storage, address: &Addr, role: Role) -> bool {
+    ROLES.has(storage, (role.as_str(), address))
+}
+
+// event attribute of a setting that may be unset
+pub fn or_none(value: Option<impl ToString>) -> String {
+    value.map_or_else(|| "none".to_string(), |value| value.to_string())
+}
+
+pub fn is_paused(storage: &dyn Storage, feature: Feature) -> bool {
+    PAUSED.has(storage, feature.as_str())
+}
+
+pub fn ensure_not_paused(
+    storage: &dyn Storage,
+    feature: Feature,
+) -> Result<(), ContractError> {
+    match PAUSED.may_load(storage, feature.as_str())? {
+        Some(pause) => Err(ContractError::Paused {
+            feature,
+            reason: pause.reason,
+        }),
+        None => Ok(()),
+    }
+}
+
+pub fn ensure_role(
+    storage: &dyn Storage,
+    sender: &Addr,
+    role: Role,
+) -> Result<(), ContractError> {
+    if !has_role(storage, sender, role) {
+        return Err(ContractError::MissingRole {
+            sender: sender.clone(),
+            role,
+        });
+    }
+    Ok(())
+}
+
+pub fn role_holders(storage: &dyn Storage, role: Role) -> StdResult<Vec<Addr>> {
+    ROLES
+        .prefix(role.as_str())
+        .keys(storage, None, None, Order::Ascending)
+        .collect()
+}
+
+// admins are the holders of any operational role, they share the operator
+// part of the revenue
+pub fn admins(storage: &dyn Storage) -> StdResult<Vec<Addr>> {
+    let mut admins = vec![];
+    for role in Role::OPERATIONAL {
+        admins.extend(role_holders(storage, role)?);
+    }
+    admins.sort();
+    admins.dedup();
+    Ok(admins)
+}
+
+pub fn is_admin(storage: &dyn Storage, address: &Addr) -> bool {
+    Role::OPERATIONAL
+        .into_iter()
+        .any(|role| has_role(storage, address, role))
+}
+
+pub fn grant_roles(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    roles: &[Role],
+) -> StdResult<()> {
+    for role in roles {
+        ROLES.save(storage, (role.as_str(), address), &Empty {})?;
+    }
+    Ok(())
+}
+
+pub fn revoke_roles(storage: &mut dyn Storage, address: &Addr, roles: &[Role]) {
+    for role in roles {
+        ROLES.remove(storage, (role.as_str(), address));
+    }
+}
+
+pub fn load_game(
+    storage: &dyn Storage,
+    game_id: &str,
+) -> Result<Game, ContractError> {
+    GAMES.may_load(storage, game_id)?.ok_or_else(|| {
+        ContractError::GameNotFound {
+            game_id: game_id.to_string(),
+        }
+    })
+}
+
+// sum of every prize pool, credit escrow and held payout in `denom`, the
+// rest of the contract balance in that denom is unaccounted surplus
+pub fn accounted_balance(
+    storage: &dyn Storage,
+    denom: &str,
+) -> StdResult<u128> {
+    let asset = AssetInfo::Native(denom.to_string());
+    let pools = GAMES
+        .range(storage, None, None, Order::Ascending)
+        .filter(|item| match item {
+            Ok((_, game)) => game.asset == asset,
+            Err(_) => true,
+        })
+        .map(|item| {
+            let (game_id, _) = item?;
+            let escrow = CREDIT_ESCROW
+                .may_load(storage, &game_id)?
+                .unwrap_or_default();
+            Ok(PRIZE_POOL.load(storage, &game_id)? + escrow)
+        })
+        .sum::<StdResult<u128>>()?;
+    let asset_pools = ASSET_POOLS
+        .range(storage, None, None, Order::Ascending)
+        .filter(|item| match item {
+            Ok(((_, key), _)) => *key == asset.key(),
+            Err(_) => true,
+        })
+        .map(|item| item.map(|(_, amount)| amount))
+        .sum::<StdResult<u128>>()?;
+    let held = HELD_PAYOUTS
+        .range(storage, None, None, Order::Ascending)
+        .filter(|item| match item {
+            Ok(((_, key), _)) => *key == asset.key(),
+            Err(_) => true,
+        })
+        .map(|item| item.map(|(_, amount)| amount))
+        .sum::<StdResult<u128>>()?;
+    Ok(pools + asset_pools + held)
+}
+
+pub fn unaccounted_surplus(
+    deps: Deps,
+    env: &Env,
+    denom: &str,
+) -> StdResult<u128> {
+    let balance = deps
+        .querier
+        .query_balance(&env.contract.address, denom)?
+        .amount
+        .u128();
+    let accounted = accounted_balance(deps.storage, denom)?;
+    Ok(balance.saturating_sub(accounted))
+}
+
+// what a player paid a message with
+pub enum Funds {
+    Native(Vec<Coin>),
+    // tokens of a CW20 `Receive`
+    Cw20 { token: Addr, amount: u128 },
+}
+
+impl Funds {
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Funds::Native(coins) => {
+                coins.iter().all(|coin| coin.amount.is_zero())
+            }
+            Funds::Cw20 { amount, .. } => *amount == 0,
+        }
+    }
+}
+
+pub struct Payment {
+    // part of the funds that pays for the game
+    pub tokens: u128,
+    // funds to send back to the player
+    pub change: Vec<Asset>,
+}
+
+// checks the funds sent to `Play` against the game price
+pub fn take_payment(
+    mode: &PaymentMode,
+    funds: &Funds,
+    asset: &AssetInfo,
+    price: u128,
+) -> Result<Payment, ContractError> {
+    let mut sent = 0;
+    let mut change = vec![];
+    match funds {
+        Funds::Native(coins) => {
+            for fund in coins.iter().filter(|fund| !fund.amount.is_zero()) {
+                if *asset == AssetInfo::Native(fund.denom.clone()) {
+                    sent += fund.amount.u128();
+                } else if *mode == PaymentMode::Exact {
+                    return Err(ContractError::UnexpectedDenom {
+                        denom: fund.denom.clone(),
+                    });
+                } else {
+                    change.push(Asset::new(
+                        AssetInfo::Native(fund.denom.clone()),
+                        fund.amount.u128(),
+                    ));
+                }
+            }
+        }
+        // only the token of the game is taken, the transfer of any other
+        // one fails with the message
+        Funds::Cw20 { token, amount } => {
+            if *asset != AssetInfo::Cw20(token.clone()) {
+                return Err(ContractError::UnexpectedDenom {
+                    denom: token.to_string(),
+                });
+            }
+            sent = *amount;
+        }
+    }
+    if sent < price {
+        return Err(ContractError::InsufficientPayment {
+            required: price,
+            sent,
+        });
+    }
+    if sent > price {
+        if *mode == PaymentMode::Exact {
+            return Err(ContractError::Overpayment {
+                required: price,
+                sent,
+            });
+        }
+        change.insert(0, Asset::new(asset.clone(), sent - price));
+    }
+    Ok(Payment {
+        tokens: price,
+        change,
+    })
+}
+
+// amount of the game asset sent to fund a prize pool, with nothing else
+pub fn must_pay_asset(
+    sender: &Addr,
+    funds: Funds,
+    asset: &AssetInfo,
+) -> Result<u128, ContractError> {
+    match (funds, asset) {
+        (Funds::Native(funds), AssetInfo::Native(denom)) => {
+            let info = MessageInfo {
+                sender: sender.clone(),
+                funds,
+            };
+            Ok(cw_utils::must_pay(&info, denom)?.u128())
+        }
+        (Funds::Cw20 { token, amount }, asset)
+            if *asset == AssetInfo::Cw20(token.clone()) =>
+        {
+            Ok(amount)
+        }
+        (Funds::Cw20 { token, .. }, _) => Err(ContractError::UnexpectedDenom {
+            denom: token.to_string(),
+        }),
+        // CW20 games are funded through a `Receive`
+        (Funds::Native(funds), _) => match funds.first() {
+            Some(fund) => Err(ContractError::UnexpectedDenom {
+                denom: fund.denom.clone(),
+            }),
+            None => Err(PaymentError::NoFunds {}.into()),
+        },
+    }
+}
+
+pub fn transfer_msg(
+    asset: &AssetInfo,
+    recipient: &Addr,
+    amount: u128,
+) -> StdResult<CosmosMsg> {
+    Ok(match asset {
+        AssetInfo::Native(denom) => BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: coins(amount, denom),
+        }
+        .into(),
+        AssetInfo::Cw20(token) => WasmMsg::Execute {
+            contract_addr: token.to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount: Uint128::new(amount),
+            })?,
+            funds: vec![],
+        }
+        .into(),
+    })
+}
+
+pub fn burn_msg(asset: &AssetInfo, amount: u128) -> StdResult<CosmosMsg> {
+    Ok(match asset {
+        AssetInfo::Native(denom) => BankMsg::Burn {
+            amount: coins(amount, denom),
+        }
+        .into(),
+        AssetInfo::Cw20(token) => WasmMsg::Execute {
+            contract_addr: token.to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Burn {
+                amount: Uint128::new(amount),
+            })?,
+            funds: vec![],
+        }
+        .into(),
+    })
+}
+
+pub fn validate_asset(
+    api: &dyn Api,
+    asset: &AssetInfo,
+) -> Result<(), ContractError> {
+    if let AssetInfo::Cw20(token) = asset {
+        api.addr_validate(token.as_str())?;
+    }
+    Ok(())
+}
+
+pub fn update_player_stats(
+    storage: &mut dyn Storage,
+    game_id: &str,
+    player: &Addr,
+    update: impl FnOnce(&mut PlayerStats),
+) -> StdResult<PlayerStats> {
+    PLAYER_STATS.update(storage, (game_id, player), |stats| {
+        let mut stats = stats.unwrap_or_default();
+        update(&mut stats);
+        Ok(stats)
+    })
+}
+
+// price of a game in `asset`, `None` when the game does not accept it
+pub fn asset_price(
+    storage: &dyn Storage,
+    game_id: &str,
+    game: &Game,
+    asset: &AssetInfo,
+) -> StdResult<Option<u128>> {
+    if *asset == game.asset {
+        return Ok(Some(game.price));
+    }
+    ACCEPTED_ASSETS.may_load(storage, (game_id, &asset.key()))
+}
+
+// the accepted asset `funds` pay a game in, preferring the game asset.
+// Without one the game asset is returned so the payment is refused with
+// its price
+pub fn payment_asset(
+    storage: &dyn Storage,
+    game_id: &str,
+    game: &Game,
+    funds: &Funds,
+) -> StdResult<(AssetInfo, u128)> {
+    let sent: Vec<_> = match funds {
+        Funds::Native(coins) => coins
+            .iter()
+            .filter(|coin| !coin.amount.is_zero())
+            .map(|coin| AssetInfo::Native(coin.denom.clone()))
+            .collect(),
+        Funds::Cw20 { token, .. } => vec![AssetInfo::Cw20(token.clone())],
+    };
+    if !sent.contains(&game.asset) {
+        for asset in sent {
+            if let Some(price) = asset_price(storage, game_id, game, &asset)? {
+                return Ok((asset, price));
+            }
         }
     }
-    return false;
+    Ok((game.asset.clone(), game.price))
+}
+
+// every prize pool of a game, the one in the game asset first
+pub fn prize_pools(
+    storage: &dyn Storage,
+    game_id: &str,
+    game: &Game,
+) -> StdResult<Vec<(AssetInfo, u128)>> {
+    let mut pools =
+        vec![(game.asset.clone(), PRIZE_POOL.load(storage, game_id)?)];
+    for item in
+        ASSET_POOLS
+            .prefix(game_id)
+            .range(storage, None, None, Order::Ascending)
+    {
+        let (key, amount) = item?;
+        pools.push((AssetInfo::from_key(&key), amount));
+    }
+    Ok(pools)
 }
 
-pub fn query_arcade_balance(
-    deps: &DepsMut,
-    env: Env,
-) -> Result<u128, StdError> {
-    let denom = ARCADE_DENOM.load(deps.storage)?;
-    let address = env.contract.address.to_string();
-    let balance_query = BankQuery::Balance { denom, address };
-    let balance_response: BalanceResponse =
-        deps.querier.query(&balance_query.into())?;
-    let balance_u128 = balance_response.amount.amount.u128();
-    Ok(balance_u128)
+// returns the new prize pool in `asset`
+pub fn add_to_prize_pool(
+    storage: &mut dyn Storage,
+    game_id: &str,
+    game: &Game,
+    asset: &AssetInfo,
+    amount: u128,
+) -> StdResult<u128> {
+    let add = |pool: Option<u128>| -> StdResult<_> {
+        Ok(pool.unwrap_or_default() + amount)
+    };
+    if *asset == game.asset {
+        PRIZE_POOL.update(storage, game_id, add)
+    } else {
+        ASSET_POOLS.update(storage, (game_id, &asset.key()), add)
+    }
+}
+
+// moves `amount` out of the game prize pool in `asset` and sends it to the
+// player of `entry`, or holds it for them while payouts are paused. Player
+// winnings are counted in the game asset only
+#[allow(clippy::too_many_arguments)]
+fn pay_prize(
+    storage: &mut dyn Storage,
+    game_id: &str,
+    game: &Game,
+    season_id: u32,
+    asset: &AssetInfo,
+    entry: &User,
+    rank: usize,
+    amount: u128,
+) -> StdResult<(Option<CosmosMsg>, Event)> {
+    let recipient = &entry.address;
+    if *asset == game.asset {
+        let prize_pool = PRIZE_POOL.load(storage, game_id)?;
+        PRIZE_POOL.save(storage, game_id, &(prize_pool - amount))?;
+        let distributed = TOTAL_PRICE_DISTRIBUTED.load(storage, game_id)?;
+        TOTAL_PRICE_DISTRIBUTED.save(
+            storage,
+            game_id,
+            &(distributed + amount),
+        )?;
+        update_player_stats(storage, game_id, recipient, |stats| {
+            stats.total_winnings += amount
+        })?;
+    } else {
+        let key = asset.key();
+        let prize_pool = ASSET_POOLS.load(storage, (game_id, &key))?;
+        ASSET_POOLS.save(storage, (game_id, &key), &(prize_pool - amount))?;
+        ASSET_DISTRIBUTED.update(storage, (game_id, &key), |total| {
+            StdResult::Ok(total.unwrap_or_default() + amount)
+        })?;
+    }
+    let held = is_paused(storage, Feature::Payouts);
+    let msg = if held {
+        HELD_PAYOUTS.update(storage, (recipient, &asset.key()), |held| {
+            StdResult::Ok(held.unwrap_or_default() + amount)
+        })?;
+        None
+    } else {
+        Some(transfer_msg(asset, recipient, amount)?)
+    };
+    let event = Event::new("arcade_prize_paid")
+        .add_attribute("game_id", game_id)
+        .add_attribute("season_id", season_id.to_string())
+        .add_attribute("recipient", recipient)
+        .add_attribute("rank", rank.to_string())
+        .add_attribute("score", entry.score.to_string())
+        .add_attribute("asset", asset.to_string())
+        .add_attribute("amount", amount.to_string())
+        .add_attribute("held", held.to_string());
+    Ok((msg, event))
 }
 
-pub fn send_coins(deps: &mut DepsMut, user: &User, env: Env) -> Result<Response, ContractError> {
-    let denom = ARCADE_DENOM.load(deps.storage).expect("Denom was not fetched");
-    let balance = query_arcade_balance(deps, env).expect("Balance was not fetched");
-    if balance == 0 {
+pub fn send_coins(
+    deps: &mut DepsMut,
+    game_id: &str,
+    season_id: u32,
+    user: &User,
+) -> Result<Response, ContractError> {
+    let game = load_game(deps.storage, game_id)?;
+    let pools: Vec<_> = prize_pools(deps.storage, game_id, &game)?
+        .into_iter()
+        .filter(|(_, amount)| *amount > 0)
+        .collect();
+    if pools.is_empty() {
         // return Err(ContractError::NoFunds); // this break the future calls logic
         return Ok(Response::new());
     }
-    let distributed =
-        TOTAL_PRICE_DISTRIBUTED.load(deps.storage);
-    let total_distributed = distributed.unwrap() + balance;
-    TOTAL_PRICE_DISTRIBUTED
-        .save(deps.storage, &total_distributed).expect("Total price was not updated");
-
-    let msg = BankMsg::Send {
-        to_address: user.address.to_string(),
-        amount: coins(
-            balance.into(),
-            &denom,
-        ),
-    };
 
-    let res = Response::new()
-        .add_message(msg)
+    let mut res = Response::new()
         .add_attribute("action", "send_coins")
-        .add_attribute("sender", user.address.clone())
-        .add_attribute("amount", balance.to_string());
+        .add_attribute("game_id", game_id)
+        .add_attribute("sender", user.address.clone());
+    // `held` is set when any of the assets was held
+    let mut held = false;
+    for (asset, amount) in pools {
+        // paid to the new #1
+        let (msg, event) = pay_prize(
+            deps.storage,
+            game_id,
+            &game,
+            season_id,
+            &asset,
+            user,
+            1,
+            amount,
+        )?;
+        held |= msg.is_none();
+        res = res
+            .add_messages(msg)
+            .add_attributes([prize_attribute(&game, asset, amount)])
+            .add_event(event);
+    }
+    Ok(res.add_attribute("held", held.to_string()))
+}
+
+// `amount` for prizes in the game asset, `prize` with the asset for others
+fn prize_attribute(game: &Game, asset: AssetInfo, amount: u128) -> Attribute {
+    if asset == game.asset {
+        attr("amount", amount.to_string())
+    } else {
+        attr("prize", Asset::new(asset, amount).to_string())
+    }
+}
+
+// cost of `count` credits with the best bulk discount they qualify for
+pub fn credits_cost(
+    discounts: &[BulkDiscount],
+    price: u128,
+    count: u32,
+) -> StdResult<u128> {
+    let discount_bps = discounts
+        .iter()
+        .filter(|discount| discount.min_count <= count)
+        .map(|discount| discount.discount_bps)
+        .max()
+        .unwrap_or_default();
+    let total = Uint128::new(price).checked_mul(Uint128::from(count))?;
+    Ok(apply_discount(total.u128(), discount_bps))
+}
+
+pub fn apply_discount(price: u128, discount_bps: u16) -> u128 {
+    price
+        - Uint128::new(price)
+            .multiply_ratio(discount_bps, TOTAL_BPS)
+            .u128()
+}
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+// uses of a pass token on the day of `time`, uses of earlier days no
+// longer count
+pub fn nft_pass_uses_on(
+    uses: Option<NftPassUses>,
+    time: Timestamp,
+) -> NftPassUses {
+    let day = time.seconds() / SECONDS_PER_DAY;
+    uses.filter(|uses| uses.day == day)
+        .unwrap_or(NftPassUses { day, count: 0 })
+}
 
+// counts a play with the pass token after checking that `player` owns it
+// and has a play left on it today, returns the discount of the pass
+pub fn use_nft_pass(
+    deps: &mut DepsMut,
+    env: &Env,
+    game_id: &str,
+    player: &Addr,
+    token_id: &str,
+) -> Result<u16, ContractError> {
+    let pass = NFT_PASSES.may_load(deps.storage, game_id)?.ok_or(
+        ContractError::NoNftPass {
+            game_id: game_id.to_string(),
+        },
+    )?;
+    let owner: OwnerOfResponse = deps.querier.query_wasm_smart(
+        &pass.contract,
+        &Cw721QueryMsg::OwnerOf {
+            token_id: token_id.to_string(),
+            include_expired: None,
+        },
+    )?;
+    if owner.owner != *player {
+        return Err(ContractError::NotTokenOwner {
+            token_id: token_id.to_string(),
+        });
+    }
+    let key = (game_id, token_id);
+    let mut uses = nft_pass_uses_on(
+        NFT_PASS_USES.may_load(deps.storage, key)?,
+        env.block.time,
+    );
+    if uses.count >= pass.daily_plays {
+        return Err(ContractError::NftPlaysUsed {
+            token_id: token_id.to_string(),
+            daily_plays: pass.daily_plays,
+        });
+    }
+    uses.count += 1;
+    NFT_PASS_USES.save(deps.storage, key, &uses)?;
+    Ok(pass.discount_bps)
+}
+
+pub fn validate_nft_pass(
+    api: &dyn Api,
+    pass: &NftPass,
+) -> Result<(), ContractError> {
+    api.addr_validate(pass.contract.as_str())?;
+    if pass.discount_bps > TOTAL_BPS {
+        return Err(ContractError::InvalidNftDiscount {
+            discount_bps: pass.discount_bps,
+        });
+    }
+    Ok(())
+}
+
+pub fn validate_bulk_discounts(
+    discounts: &[BulkDiscount],
+) -> Result<(), ContractError> {
+    match discounts
+        .iter()
+        .find(|discount| discount.discount_bps > TOTAL_BPS)
+    {
+        Some(discount) => Err(ContractError::InvalidBulkDiscount {
+            discount_bps: discount.discount_bps,
+        }),
+        None => Ok(()),
+    }
+}
+
+// takes one credit, returning the share of the escrow it was paid with.
+// Rounding leftovers go with the last credit
+pub fn spend_credit(
+    storage: &mut dyn Storage,
+    game_id: &str,
+    player: &Addr,
+) -> StdResult<Option<u128>> {
+    let Some(mut credits) = CREDITS.may_load(storage, (game_id, player))?
+    else {
+        return Ok(None);
+    };
+    let value = credits.paid / u128::from(credits.count);
+    credits.count -= 1;
+    credits.paid -= value;
+    if credits.count == 0 {
+        CREDITS.remove(storage, (game_id, player));
+    } else {
+        CREDITS.save(storage, (game_id, player), &credits)?;
+    }
+    CREDIT_ESCROW.update(storage, game_id, |escrow| -> StdResult<_> {
+        Ok(escrow.unwrap_or_default() - value)
+    })?;
+    Ok(Some(value))
+}
+
+pub fn validate_payout_schedule(schedule: &[u16]) -> Result<(), ContractError> {
+    let total = schedule.iter().map(|bps| u32::from(*bps)).sum();
+    if total > u32::from(TOTAL_BPS) {
+        return Err(ContractError::InvalidPayoutSchedule { total });
+    }
+    Ok(())
+}
+
+// pays the prize pool out to the scoreboard by the game payout schedule,
+// shares of empty ranks stay in the pool
+pub fn distribute_prizes(
+    deps: &mut DepsMut,
+    game_id: &str,
+    season_id: u32,
+) -> Result<Response, ContractError> {
+    let game = load_game(deps.storage, game_id)?;
+    let schedule = game.payout_schedule.clone().ok_or_else(|| {
+        ContractError::NoPayoutSchedule {
+            game_id: game_id.to_string(),
+        }
+    })?;
+    let pools = prize_pools(deps.storage, game_id, &game)?;
+    let ranked = ranked_scoreboard(deps.storage, game_id)?;
+
+    let mut res = Response::new()
+        .add_attribute("action", "distribute")
+        .add_attribute("game_id", game_id);
+    for (rank, (entry, bps)) in ranked.iter().zip(schedule).enumerate() {
+        let amounts: Vec<_> = pools
+            .iter()
+            .map(|(asset, pool)| {
+                let amount =
+                    Uint128::new(*pool).multiply_ratio(bps, TOTAL_BPS).u128();
+                (asset.clone(), amount)
+            })
+            .filter(|(_, amount)| *amount > 0)
+            .collect();
+        if amounts.is_empty() {
+            continue;
+        }
+        res = res
+            .add_attribute("rank", (rank + 1).to_string())
+            .add_attribute("recipient", entry.user.address.clone());
+        let mut held = false;
+        for (asset, amount) in amounts {
+            let (msg, event) = pay_prize(
+                deps.storage,
+                game_id,
+                &game,
+                season_id,
+                &asset,
+                &entry.user,
+                rank + 1,
+                amount,
+            )?;
+            held |= msg.is_none();
+            res = res
+                .add_messages(msg)
+                .add_attributes([prize_attribute(&game, asset, amount)])
+                .add_event(event);
+        }
+        res = res.add_attribute("held", held.to_string());
+    }
     Ok(res)
 }
 
+pub fn season_ended(
+    duration: &Option<SeasonDuration>,
+    season: &Season,
+    block: &BlockInfo,
+) -> bool {
+    // measured from the start so the largest durations cannot overflow
+    match duration {
+        Some(SeasonDuration::Seconds(seconds)) => {
+            block.time.nanos().saturating_sub(season.start_time.nanos())
+                >= seconds.saturating_mul(1_000_000_000)
+        }
+        Some(SeasonDuration::Blocks(blocks)) => {
+            block.height.saturating_sub(season.start_height) >= *blocks
+        }
+        None => false,
+    }
+}
+
+pub fn trophy_token_id(id: u64) -> String {
+    format!("arcade-{id}")
+}
+
+// adds the mint of a trophy for `owner` to `res`, unless no trophy contract
+// is configured. the mint replies with the trophy number as id, the minted
+// event is only emitted once the trophy contract confirmed it
+pub fn mint_trophy(
+    storage: &mut dyn Storage,
+    res: Response,
+    owner: &Addr,
+    metadata: TrophyMetadata,
+) -> StdResult<Response> {
+    let config = match TROPHY_CONFIG.may_load(storage)? {
+        Some(config) => config,
+        None => return Ok(res),
+    };
+    let id = TROPHY_COUNTER.may_load(storage)?.unwrap_or_default() + 1;
+    TROPHY_COUNTER.save(storage, &id)?;
+    let mint = WasmMsg::Execute {
+        contract_addr: config.contract.to_string(),
+        msg: to_json_binary(&Cw721ExecuteMsg::Mint {
+            token_id: trophy_token_id(id),
+            owner: owner.to_string(),
+            token_uri: None,
+            extension: metadata.clone(),
+        })?,
+        funds: vec![],
+    };
+    let pending = PendingTrophy {
+        owner: owner.clone(),
+        metadata,
+    };
+    PENDING_TROPHIES.save(storage, id, &pending)?;
+    Ok(res.add_submessage(SubMsg::reply_always(mint, id)))
+}
+
+pub fn start_season(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    game_id: &str,
+    id: u32,
+) -> StdResult<Season> {
+    let season = Season {
+        id,
+        start_height: block.height,
+        start_time: block.time,
+        games_at_start: GAME_COUNTER.load(storage, game_id)?,
+        distributed_at_start: TOTAL_PRICE_DISTRIBUTED.load(storage, game_id)?,
+    };
+    CURRENT_SEASON.save(storage, game_id, &season)?;
+    Ok(season)
+}
+
+// archives the current season and starts the next one with an empty
+// scoreboard, games with a payout schedule are paid out first
+pub fn end_season(
+    deps: &mut DepsMut,
+    env: &Env,
+    game_id: &str,
+) -> Result<Response, ContractError> {
+    let game = load_game(deps.storage, game_id)?;
+    let season = CURRENT_SEASON.load(deps.storage, game_id)?;
+    let mut res = match game.payout_schedule {
+        Some(_) => distribute_prizes(deps, game_id, season.id)?,
+        None => Response::new(),
+    };
+
+    let scoreboard = ranked_scoreboard(deps.storage, game_id)?;
+    let places = TROPHY_CONFIG
+        .may_load(deps.storage)?
+        .map_or(0, |config| config.season_places);
+    for (rank, entry) in scoreboard.iter().take(places.into()).enumerate() {
+        let metadata = TrophyMetadata {
+            kind: TrophyKind::SeasonPlacement,
+            game_id: game_id.to_string(),
+            score: entry.user.score,
+            rank: rank as u32 + 1,
+            season_id: season.id,
+            time: env.block.time,
+        };
+        res = mint_trophy(deps.storage, res, &entry.user.address, metadata)?;
+    }
+    let games = GAME_COUNTER.load(deps.storage, game_id)?;
+    let distributed = TOTAL_PRICE_DISTRIBUTED.load(deps.storage, game_id)?;
+    let record = SeasonRecord {
+        id: season.id,
+        start_height: season.start_height,
+        start_time: season.start_time,
+        end_height: env.block.height,
+        end_time: env.block.time,
+        winner: scoreboard.first().cloned(),
+        scoreboard,
+        games_played: games - season.games_at_start,
+        distributed: distributed - season.distributed_at_start,
+    };
+    let ended = Event::new("arcade_season_ended")
+        .add_attribute("game_id", game_id)
+        .add_attribute("season_id", season.id.to_string())
+        .add_attribute(
+            "winner",
+            or_none(record.winner.as_ref().map(|entry| &entry.user.address)),
+        )
+        .add_attribute(
+            "winning_score",
+            or_none(record.winner.as_ref().map(|entry| entry.user.score)),
+        )
+        .add_attribute("games_played", record.games_played.to_string())
+        .add_attribute("distributed", record.distributed.to_string());
+    SEASONS.save(deps.storage, (game_id, season.id), &record)?;
+    TOP_USERS.save(deps.storage, game_id, &vec![])?;
+    let next = start_season(deps.storage, &env.block, game_id, season.id + 1)?;
+
+    Ok(res
+        .add_event(ended.add_attribute("next_season_id", next.id.to_string()))
+        .add_attribute("action", "end_season")
+        .add_attribute("game_id", game_id)
+        .add_attribute("ended_season_id", season.id.to_string())
+        .add_attribute("season_id", next.id.to_string()))
+}
+
+// ends the current season when its duration has passed, it is checked
+// lazily by every play and score submission
+pub fn advance_season(
+    deps: &mut DepsMut,
+    env: &Env,
+    game_id: &str,
+) -> Result<Response, ContractError> {
+    let game = load_game(deps.storage, game_id)?;
+    let season = CURRENT_SEASON.load(deps.storage, game_id)?;
+    if season_ended(&game.season_duration, &season, &env.block) {
+        end_season(deps, env, game_id)
+    } else {
+        Ok(Response::new())
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use cosmwasm_std::Addr;
+    use cosmwasm_std::testing::MockApi;
+    use cosmwasm_std::{coin, Addr, Timestamp, Uint64};
 
     use super::*;
-    use std::cmp::Reverse;
-    use std::collections::BinaryHeap;
+    use crate::state::Share;
 
-    fn test_user(name: &str, score: u16) -> User {
-        User {
-            name: name.to_string(),
-            address: Addr::unchecked("test"),
-            score: Reverse(score),
+    fn test_user(name: &str, score: u64, seq: u64) -> ScoreEntry {
+        ScoreEntry {
+            user: User {
+                name: name.to_string(),
+                address: Addr::unchecked("test"),
+                score: Uint64::new(score),
+            },
+            height: seq,
+            time: Timestamp::from_seconds(seq),
+            seq,
         }
     }
 
     #[test]
     fn test_user_is_top() {
-        let mut heap = BinaryHeap::new();
-        let user1 = test_user("user1", 10);
-        let user2 = test_user("user2", 20);
-        let user3 = test_user("user3", 30);
+        let order = ScoreOrder::HigherIsBetter;
+        let mut scoreboard = vec![];
+        let user1 = test_user("user1", 10, 1);
+        let user2 = test_user("user2", 20, 2);
+        let user3 = test_user("user3", 30, 3);
+
+        assert!(!user_is_top(&order, &scoreboard, &user1));
+
+        scoreboard.push(user1.clone());
+        scoreboard.push(user2.clone());
 
-        heap.push(user1.clone());
-        heap.push(user2.clone());
+        assert!(!user_is_top(&order, &scoreboard, &user1));
+        assert!(!user_is_top(&order, &scoreboard, &user2));
+        assert!(user_is_top(&order, &scoreboard, &user3));
 
-        assert!(!user_is_top(&heap, &user1));
-        assert!(!user_is_top(&heap, &user2));
-        assert!(user_is_top(&heap, &user3));
+        scoreboard.push(user3.clone());
+        assert!(!user_is_top(&order, &scoreboard, &user3));
+    }
+
+    #[test]
+    fn test_user_is_top_lower_is_better() {
+        let order = ScoreOrder::LowerIsBetter;
+        let scoreboard =
+            vec![test_user("user1", 30, 1), test_user("user2", 20, 2)];
 
-        heap.push(user3.clone());
-        assert!(!user_is_top(&heap, &user3));
+        assert!(user_is_top(&order, &scoreboard, &test_user("user3", 10, 3)));
+        assert!(!user_is_top(
+            &order,
+            &scoreboard,
+            &test_user("user3", 25, 3)
+        ));
+        assert!(!user_is_top(
+            &order,
+            &scoreboard,
+            &test_user("user3", 20, 3)
+        ));
+    }
+
+    #[test]
+    fn test_user_is_top_tie() {
+        let order = ScoreOrder::HigherIsBetter;
+        let scoreboard =
+            vec![test_user("user1", 30, 1), test_user("user2", 20, 2)];
+
+        // a later submission of the top score does not take the top
+        assert!(!user_is_top(
+            &order,
+            &scoreboard,
+            &test_user("user3", 30, 3)
+        ));
+        // an earlier one would
+        assert!(user_is_top(&order, &scoreboard, &test_user("user0", 30, 0)));
+    }
+
+    #[test]
+    fn test_rank_ties() {
+        let names = |order: ScoreOrder| {
+            let mut scoreboard = vec![
+                test_user("user3", 20, 3),
+                test_user("user2", 30, 2),
+                test_user("user4", 20, 4),
+                test_user("user1", 20, 1),
+            ];
+            scoreboard.sort_by(|a, b| compare_entries(&order, a, b));
+            scoreboard
+                .into_iter()
+                .map(|entry| entry.user.name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(ScoreOrder::HigherIsBetter),
+            vec!["user2", "user1", "user3", "user4"]
+        );
+        assert_eq!(
+            names(ScoreOrder::LowerIsBetter),
+            vec!["user1", "user3", "user4", "user2"]
+        );
+    }
+
+    fn split(
+        prize_pool_bps: u16,
+        treasury_bps: u16,
+        operators_bps: u16,
+        burn_bps: u16,
+    ) -> RevenueSplit {
+        RevenueSplit {
+            prize_pool_bps,
+            treasury: Some(Share {
+                address: Addr::unchecked("treasury"),
+                bps: treasury_bps,
+            }),
+            developer: None,
+            operators_bps,
+            burn_bps,
+        }
+    }
+
+    #[test]
+    fn test_split_revenue() {
+        let shares = split_revenue(&split(5000, 2000, 2500, 500), 1000, 2);
+        assert_eq!(
+            shares,
+            RevenueShares {
+                prize_pool: 500,
+                treasury: 200,
+                developer: 0,
+                operators: 125,
+                burn: 50,
+                dust: 0,
+            }
+        );
+
+        // 333 * 25% = 83 for 2 operators, 1 coin of it is dust
+        let shares = split_revenue(&split(5000, 2000, 2500, 500), 333, 2);
+        assert_eq!(shares.prize_pool, 166);
+        assert_eq!(shares.treasury, 66);
+        assert_eq!(shares.operators, 41);
+        assert_eq!(shares.burn, 16);
+        assert_eq!(shares.dust, 3);
+
+        // without operators their share is dust
+        let shares = split_revenue(&split(5000, 2000, 2500, 500), 1000, 0);
+        assert_eq!(shares.operators, 0);
+        assert_eq!(shares.dust, 250);
+    }
+
+    #[test]
+    fn test_validate_revenue_split() {
+        let api = MockApi::default();
+        assert!(
+            validate_revenue_split(&api, &split(5000, 2000, 2500, 500)).is_ok()
+        );
+        assert_eq!(
+            validate_revenue_split(&api, &split(5000, 2000, 2500, 0)),
+            Err(ContractError::InvalidRevenueSplit { total: 9500 })
+        );
+    }
+
+    #[test]
+    fn test_take_payment() {
+        let aconst = AssetInfo::Native("aconst".to_string());
+        let native = |funds: &[(u128, &str)]| {
+            Funds::Native(
+                funds
+                    .iter()
+                    .map(|(amount, denom)| coin(*amount, *denom))
+                    .collect(),
+            )
+        };
+        let refund = PaymentMode::RefundChange;
+        let payment = take_payment(
+            &refund,
+            &native(&[(5, "aconst"), (7, "uatom")]),
+            &aconst,
+            2,
+        )
+        .unwrap();
+        assert_eq!(payment.tokens, 2);
+        assert_eq!(
+            payment.change,
+            vec![
+                Asset::new(aconst.clone(), 3),
+                Asset::new(AssetInfo::Native("uatom".to_string()), 7)
+            ]
+        );
+        assert_eq!(
+            take_payment(&refund, &native(&[(1, "aconst")]), &aconst, 2).err(),
+            Some(ContractError::InsufficientPayment {
+                required: 2,
+                sent: 1
+            })
+        );
+        assert_eq!(
+            take_payment(&refund, &native(&[]), &aconst, 2).err(),
+            Some(ContractError::InsufficientPayment {
+                required: 2,
+                sent: 0
+            })
+        );
+
+        let exact = PaymentMode::Exact;
+        let payment =
+            take_payment(&exact, &native(&[(2, "aconst")]), &aconst, 2)
+                .unwrap();
+        assert!(payment.change.is_empty());
+        assert_eq!(
+            take_payment(&exact, &native(&[(3, "aconst")]), &aconst, 2).err(),
+            Some(ContractError::Overpayment {
+                required: 2,
+                sent: 3
+            })
+        );
+        assert_eq!(
+            take_payment(
+                &exact,
+                &native(&[(2, "aconst"), (1, "uatom")]),
+                &aconst,
+                2
+            )
+            .err(),
+            Some(ContractError::UnexpectedDenom {
+                denom: "uatom".to_string()
+            })
+        );
+
+        // CW20 change goes back in the same token
+        let token = AssetInfo::Cw20(Addr::unchecked("token"));
+        let cw20 = |token: &str, amount| Funds::Cw20 {
+            token: Addr::unchecked(token),
+            amount,
+        };
+        let payment =
+            take_payment(&refund, &cw20("token", 5), &token, 2).unwrap();
+        assert_eq!(payment.change, vec![Asset::new(token.clone(), 3)]);
+        assert_eq!(
+            take_payment(&refund, &cw20("other", 5), &token, 2).err(),
+            Some(ContractError::UnexpectedDenom {
+                denom: "other".to_string()
+            })
+        );
+        assert_eq!(
+            take_payment(&refund, &cw20("token", 5), &aconst, 2).err(),
+            Some(ContractError::UnexpectedDenom {
+                denom: "token".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_credits_cost() {
+        let discounts = [
+            BulkDiscount {
+                min_count: 5,
+                discount_bps: 1000,
+            },
+            BulkDiscount {
+                min_count: 10,
+                discount_bps: 2500,
+            },
+        ];
+        assert_eq!(credits_cost(&[], 10, 3).unwrap(), 30);
+        assert_eq!(credits_cost(&discounts, 10, 4).unwrap(), 40);
+        assert_eq!(credits_cost(&discounts, 10, 5).unwrap(), 45);
+        assert_eq!(credits_cost(&discounts, 10, 20).unwrap(), 150);
+        assert!(credits_cost(&[], u128::MAX, 2).is_err());
+        assert_eq!(apply_discount(u128::MAX, 10000), 0);
+        assert_eq!(
+            validate_bulk_discounts(&[BulkDiscount {
+                min_count: 1,
+                discount_bps: 10001,
+            }]),
+            Err(ContractError::InvalidBulkDiscount {
+                discount_bps: 10001
+            })
+        );
     }
 }